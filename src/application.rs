@@ -1,5 +1,7 @@
 //! 入口层：负责交互循环、命令分发与事件发布。
 use std::{
+    env,
+    fs,
     io::{self, Write},
     path::{PathBuf, Path}
 };
@@ -7,32 +9,136 @@ use crate::{
     error::{AppResult, AppError}, 
     event::{Event, EventBus}, 
     logging::Logger, 
-    persist::WorkspaceMemento, 
-    router::Router, 
-    workspace::Workspace
+    persist::{RestoreReport, WorkspaceMemento}, 
+    router::{Chain, Router}, 
+    sandbox::Sandbox,
+    workspace::{Workspace, WORKSPACE_ENV},
 };
 
 pub struct Application {
     pub router: Router,
     pub workspace: Workspace,
     pub bus: EventBus,
+    /// 启动时恢复工作区的情况
+    pub restore: RestoreReport,
 }
 
+/// 启动参数
+#[derive(Debug, Default)]
+pub struct StartOptions {
+    /// `--workspace <dir>`：工作区根目录
+    pub workspace: Option<PathBuf>,
+    /// `--sandbox`：文件操作限制在工作区根目录内
+    pub sandbox: bool,
+    /// `--allow-root <dir>`：沙箱额外允许的目录，可以给多次
+    pub allowed_roots: Vec<PathBuf>,
+    /// `--view`：查看模式，所有文件只读打开
+    pub view: bool,
+}
+
+impl StartOptions {
+    pub const USAGE: &'static str = "usage: texteditor [--workspace <dir>] [--sandbox] [--allow-root <dir>]... [--view]";
+
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> AppResult<Self> {
+        let mut opts = Self::default();
+        let mut it = args.into_iter();
+        while let Some(arg) = it.next() {
+            if arg == "--workspace" {
+                let dir = it
+                    .next()
+                    .ok_or_else(|| AppError::InvalidArgs(format!("--workspace needs a directory; {}", Self::USAGE)))?;
+                opts.workspace = Some(PathBuf::from(dir));
+            } else if let Some(dir) = arg.strip_prefix("--workspace=") {
+                opts.workspace = Some(PathBuf::from(dir));
+            } else if arg == "--sandbox" {
+                opts.sandbox = true;
+            } else if arg == "--view" {
+                opts.view = true;
+            } else if arg == "--allow-root" {
+                let dir = it
+                    .next()
+                    .ok_or_else(|| AppError::InvalidArgs(format!("--allow-root needs a directory; {}", Self::USAGE)))?;
+                opts.allowed_roots.push(PathBuf::from(dir));
+            } else {
+                return Err(AppError::InvalidArgs(format!("unknown option {}; {}", arg, Self::USAGE)));
+            }
+        }
+        Ok(opts)
+    }
+}
+
+/// 非空且不为 "0" 时开启路径沙箱
+pub const SANDBOX_ENV: &str = "TEXTEDITOR_SANDBOX";
+/// 沙箱额外允许的目录列表
+pub const ALLOWED_ROOTS_ENV: &str = "TEXTEDITOR_ALLOWED_ROOTS";
+
 impl Application {
-    pub fn new() -> AppResult<Self> {
-        let mut workspace = Workspace::default();
+    pub fn new(opts: &StartOptions) -> AppResult<Self> {
+        let cwd = env::current_dir()?;
+        let root = Workspace::discover_root(
+            opts.workspace.as_deref(),
+            env::var_os(WORKSPACE_ENV).as_deref(),
+            &cwd,
+        );
+        // 用绝对路径，快照里记下的文件路径就不依赖启动时所在的目录
+        let mut workspace = Workspace::new(cwd.join(root));
+
+        // 沙箱也可以由部署环境通过环境变量开启，额外目录按 PATH 的写法分隔
+        let sandbox_env = env::var_os(SANDBOX_ENV).is_some_and(|v| !v.is_empty() && v != "0");
+        if opts.sandbox || sandbox_env {
+            let mut extra = opts.allowed_roots.clone();
+            if let Some(list) = env::var_os(ALLOWED_ROOTS_ENV) {
+                extra.extend(env::split_paths(&list).filter(|p| !p.as_os_str().is_empty()));
+            }
+            workspace.set_sandbox(Some(Sandbox::new(&workspace.get_base_dir(), &extra)?));
+        }
+        workspace.set_view_only(opts.view);
         let mut bus = EventBus::new();
         bus.subscribe(Box::new(Logger::new(workspace.get_base_dir())));
 
-        let path = Path::new(".editor_workspace");
-        if path.exists() {
-            if let Ok(m) = WorkspaceMemento::load(path) {
-                workspace.from_memento(m)?;
-                println!("[info] restored workspace from .editor_workspace");
-            }
+        let path = workspace.memento_path();
+        let restore = Self::restore_workspace(&mut workspace, &path)?;
+        if restore.from_version.is_some() {
+            println!("[info] restored workspace from {}", path.display());
+        }
+        for w in &restore.warnings {
+            println!("[warn] {}", w);
         }
 
-        Ok(Self { router: Router::new(), workspace, bus })
+        // 上次异常退出或未保存就退出时留下的交换文件，交给用户决定恢复还是丢弃
+        for note in workspace.scan_swaps() {
+            println!("[warn] {}", note);
+        }
+
+        Ok(Self { router: Router::new(), workspace, bus, restore })
+    }
+
+    /// 从快照恢复工作区。快照损坏或版本不兼容时不中断启动：
+    /// 原文件改名为 `<name>.corrupt` 保留下来（退出时不会被覆盖），并给出警告。
+    fn restore_workspace(workspace: &mut Workspace, path: &Path) -> AppResult<RestoreReport> {
+        let mut report = RestoreReport::default();
+        if !path.exists() {
+            return Ok(report);
+        }
+        report.source = Some(path.to_path_buf());
+
+        match WorkspaceMemento::load(path) {
+            Ok((m, version)) => {
+                report.warnings = workspace.from_memento(m)?;
+                report.from_version = Some(version);
+                report.files = workspace.editor_count();
+            }
+            Err(e) => {
+                let mut kept = path.as_os_str().to_owned();
+                kept.push(".corrupt");
+                let kept = PathBuf::from(kept);
+                report.warnings.push(match fs::rename(path, &kept) {
+                    Ok(()) => format!("{}; kept as {}", e, kept.display()),
+                    Err(re) => format!("{}; could not keep a copy: {}", e, re),
+                });
+            }
+        }
+        Ok(report)
     }
 
     pub fn run(&mut self) -> AppResult<()> {
@@ -46,43 +152,72 @@ impl Application {
             let line = line_buf.trim();
             if line.is_empty() { continue; }
 
-            // —— 第一步：只用 &self.router 解析，拿到 handler 和 args —— //
-            let (handler, args)
-                = match self.router.resolve(line) {
-                Ok(x) => x,
-                Err(e) => {
-                    self.publish_error(e);
-                    continue;
+            if self.execute_line(line) {
+                if let Err(e) = self.save_workspace_memento() {
+                    eprintln!("[warn] failed to save workspace: {}", e);
                 }
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// 执行一行输入，支持 `;`、`&&`、`||` 串联多条命令。
+    /// 每条子命令单独打印、单独发布事件；返回值表示是否收到了退出信号。
+    pub fn execute_line(&mut self, line: &str) -> bool {
+        let chain = match Router::split_chain(line) {
+            Ok(c) => c,
+            Err(e) => {
+                self.publish_error(e);
+                return false;
+            }
+        };
+
+        // 被跳过的子命令不改变状态，与 shell 的语义一致
+        let mut last_ok = true;
+        for (cond, cmd) in chain {
+            let should_run = match cond {
+                Chain::Always => true,
+                Chain::IfOk => last_ok,
+                Chain::IfErr => !last_ok,
             };
+            if !should_run { continue; }
 
-            // —— 第二步：前一个不可变借用已结束；现在再可变借用 self 执行 —— //
-            match handler(self, &args) {
-                Ok(outcome) => {
-                    if let Some(p) = outcome.print { println!("{p}"); }
-                    if let Some(cmd) = outcome.log {
-                        self.bus.publish(Event::Command {
-                            file: self.workspace.active_file_path(),
-                            cmd
-                        });
-                    }
-                    if outcome.exit { 
-                        if let Err(e) = self.save_workspace_memento() {
-                            eprintln!("[warn] failed to save workspace: {}", e);
-                        }
-                        break; 
-                    }
+            match self.execute_command(&cmd) {
+                Ok(true) => return true,
+                Ok(false) => last_ok = true,
+                Err(e) => {
+                    self.publish_error(e);
+                    last_ok = false;
                 }
-                Err(e) => { self.publish_error(e); }
             }
         }
-        Ok(())
+        false
+    }
+
+    /// 执行单条命令并处理 Outcome；Ok(true) 表示请求退出。
+    fn execute_command(&mut self, line: &str) -> AppResult<bool> {
+        // —— 第一步：只用 &self.router 解析，拿到 handler 和 args —— //
+        let (handler, args) = self.router.resolve(line)?;
+
+        // —— 第二步：前一个不可变借用已结束；现在再可变借用 self 执行 —— //
+        let outcome = handler(self, &args)?;
+        if let Err(e) = self.workspace.write_swaps() {
+            eprintln!("[warn] failed to write swap file: {}", e);
+        }
+        if let Some(p) = outcome.print { println!("{p}"); }
+        if let Some(cmd) = outcome.log {
+            self.bus.publish(Event::Command {
+                file: self.workspace.active_file_path(),
+                cmd
+            });
+        }
+        Ok(outcome.exit)
     }
 
     pub fn save_workspace_memento(&self) -> AppResult<()> {
         let memento = self.workspace.to_memento();
-        let base: PathBuf = self.workspace.get_base_dir();
-        let path: PathBuf = base.join(".editor_workspace");
+        let path: PathBuf = self.workspace.memento_path();
         memento.save(&path)?;
         println!("[info] workspace saved to {:?}", path);
         Ok(())
@@ -105,6 +240,8 @@ mod tests {
     use tempfile::tempdir;
 
     use crate::event::Subscriber;
+    use crate::persist::Register;
+    use crate::test_util::app_with;
     use crate::error::AppResult;
 
     /// 记录所有事件的订阅者，用于测试 EventBus 行为。
@@ -127,28 +264,20 @@ mod tests {
         }
     }
 
+    type TestApp = (Application, Arc<Mutex<Vec<Event>>>, tempfile::TempDir);
+
     /// 构造一个在临时目录下运行的 Application：
-    /// - Workspace.base_dir = <tmp>/work_dir
+    /// - Workspace.base_dir = <tmp>/work_dir（交换文件等都写在这里）
     /// - EventBus 只挂一个 RecordingSubscriber（不挂 Logger）
-    fn new_test_app(
-    ) -> AppResult<(Application, Arc<Mutex<Vec<Event>>>, tempfile::TempDir)> {
+    fn new_test_app() -> AppResult<TestApp> {
         let tmp = tempdir()?; // 每个测试一个独立目录
 
-        let workspace = Workspace::default();
+        let workspace = Workspace::new(tmp.path().join("work_dir"));
 
         // EventBus：注册 RecordingSubscriber
-        let mut bus = EventBus::new();
+        let mut app = app_with(workspace);
         let shared_events: Arc<Mutex<Vec<Event>>> = Arc::new(Mutex::new(Vec::new()));
-        bus.subscribe(Box::new(RecordingSubscriber::new(shared_events.clone())));
-
-        // Router：正常初始化
-        let router = Router::new();
-
-        let app = Application {
-            router,
-            workspace,
-            bus,
-        };
+        app.bus.subscribe(Box::new(RecordingSubscriber::new(shared_events.clone())));
 
         Ok((app, shared_events, tmp))
     }
@@ -173,6 +302,44 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn start_options_parse_workspace_flag() -> AppResult<()> {
+        let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(StartOptions::from_args(args(&["--workspace", "w"]))?.workspace, Some(PathBuf::from("w")));
+        assert_eq!(StartOptions::from_args(args(&["--workspace=x"]))?.workspace, Some(PathBuf::from("x")));
+        assert!(StartOptions::from_args(args(&[]))?.workspace.is_none());
+        assert!(StartOptions::from_args(args(&["--workspace"])).is_err());
+        assert!(StartOptions::from_args(args(&["--bogus"])).is_err());
+
+        let opts = StartOptions::from_args(args(&["--sandbox", "--allow-root", "/a", "--allow-root", "/b"]))?;
+        assert!(opts.sandbox);
+        assert_eq!(opts.allowed_roots, vec![PathBuf::from("/a"), PathBuf::from("/b")]);
+        assert!(StartOptions::from_args(args(&["--view"]))?.view);
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_memento_is_kept_and_reported() -> AppResult<()> {
+        let tmp = tempdir()?;
+        let mut workspace = Workspace::new(tmp.path().join("work_dir"));
+        let path = tmp.path().join(".editor_workspace");
+        std::fs::write(&path, "{ broken")?;
+
+        let report = Application::restore_workspace(&mut workspace, &path)?;
+        assert!(report.from_version.is_none());
+        assert_eq!(report.warnings.len(), 1);
+        assert!(!path.exists());
+        assert!(tmp.path().join(".editor_workspace.corrupt").exists());
+        assert!(report.describe().contains("could not restore"));
+
+        // 旧格式照常恢复，并说明做了迁移
+        std::fs::write(&path, r#"{"open_files":{},"active":null}"#)?;
+        let report = Application::restore_workspace(&mut workspace, &path)?;
+        assert_eq!(report.from_version, Some(1));
+        assert!(report.describe().contains("migrated"));
+        Ok(())
+    }
+
     #[test]
     fn successful_command_publishes_command_event() -> AppResult<()> {
         let (mut app, events, _tmp) = new_test_app()?;
@@ -263,4 +430,144 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn chained_line_runs_and_logs_each_sub_command() -> AppResult<()> {
+        let (mut app, events, _tmp) = new_test_app()?;
+
+        let exit = app.execute_line("init a.txt; append \"one; two\" && append three");
+        assert!(!exit);
+        assert_eq!(app.workspace.show(None, None)?, "1: one; two\n2: three\n");
+
+        let evs = events.lock().unwrap();
+        let cmds: Vec<String> = evs
+            .iter()
+            .filter_map(|e| match e {
+                Event::Command { cmd, .. } => Some(cmd.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(cmds, vec!["init a.txt", "append \"one; two\"", "append \"three\""]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn conditional_chain_follows_previous_result() -> AppResult<()> {
+        let (mut app, events, _tmp) = new_test_app()?;
+
+        // edit 失败：&& 后的命令被跳过，|| 后的命令执行
+        app.execute_line("init a.txt; edit missing.txt && append skipped || append recovered");
+        assert_eq!(app.workspace.show(None, None)?, "1: recovered\n");
+
+        // 成功之后 || 分支不执行
+        app.execute_line("append ok || append skipped; append last");
+        assert_eq!(app.workspace.show(None, None)?, "1: recovered\n2: ok\n3: last\n");
+
+        let errors = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| matches!(e, Event::Error { .. }))
+            .count();
+        assert_eq!(errors, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn exit_stops_the_rest_of_the_chain() -> AppResult<()> {
+        let (mut app, _events, _tmp) = new_test_app()?;
+
+        assert!(app.execute_line("init a.txt; exit; append never"));
+        assert_eq!(app.workspace.show(None, None)?, "<empty>");
+
+        Ok(())
+    }
+
+    #[test]
+    fn line_commands_undo_back_to_original() -> AppResult<()> {
+        let (mut app, _events, _tmp) = new_test_app()?;
+        app.execute_line("init a.txt; append 1; append 2; append 3; append 4; append 5");
+        let original = app.workspace.show(None, None)?;
+
+        let cases = [
+            ("move-lines 1:2 4", "1: 3\n2: 4\n3: 1\n4: 2\n5: 5\n"),
+            ("copy-lines 4:5 0", "1: 4\n2: 5\n3: 1\n4: 2\n5: 3\n6: 4\n7: 5\n"),
+            ("delete-lines 2:4", "1: 1\n2: 5\n"),
+            ("join 1:3 -", "1: 1-2-3\n2: 4\n3: 5\n"),
+            ("split 2:1", "1: 1\n2: \n3: 2\n4: 3\n5: 4\n6: 5\n"),
+        ];
+        for (line, expected) in cases {
+            assert!(!app.execute_line(line));
+            assert_eq!(app.workspace.show(None, None)?, expected, "after `{}`", line);
+            app.workspace.undo()?;
+            assert_eq!(app.workspace.show(None, None)?, original, "undo of `{}`", line);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn registers_are_shared_between_files_and_puts_undo() -> AppResult<()> {
+        let (mut app, _events, _tmp) = new_test_app()?;
+        app.execute_line("init a.txt; append one; append two; yank 1:2");
+        app.execute_line("init b.txt; append x; append y");
+
+        // 整行内容插在第 2 行之前，col 不起作用
+        assert!(!app.execute_line("put 2:5"));
+        assert_eq!(app.workspace.show(None, None)?, "1: x\n2: one\n3: two\n4: y\n");
+        app.workspace.undo()?;
+        assert_eq!(app.workspace.show(None, None)?, "1: x\n2: y\n");
+
+        // delete 和 replace 的旧内容进 - 寄存器
+        app.execute_line("append hello");
+        app.execute_line("delete 3:1 2");
+        assert_eq!(app.workspace.get_register('-')?, &Register::Chars("he".into()));
+        app.execute_line("replace 3:1 2 LL");
+        assert_eq!(app.workspace.get_register('-')?, &Register::Chars("ll".into()));
+        assert_eq!(app.workspace.show(None, None)?, "1: x\n2: y\n3: LLo\n");
+
+        assert!(!app.execute_line("put 1:2 -"));
+        assert_eq!(app.workspace.show(None, None)?, "1: xll\n2: y\n3: LLo\n");
+        app.workspace.undo()?;
+        assert_eq!(app.workspace.show(None, None)?, "1: x\n2: y\n3: LLo\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn patch_reports_failed_files_and_applies_the_rest() -> AppResult<()> {
+        let (mut app, _events, _tmp) = new_test_app()?;
+        let base = app.workspace.get_base_dir();
+        fs::create_dir_all(&base)?;
+        fs::write(base.join("a.txt"), "one\n")?;
+        fs::write(base.join("b.txt"), "two\n")?;
+        fs::write(
+            base.join("fix.patch"),
+            "--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-one\n+ONE\n\
+             --- a/b.txt\n+++ b/b.txt\n@@ -1 +1 @@\n-two\n+TWO\n",
+        )?;
+        app.execute_line("load --readonly b.txt; init c.txt");
+
+        assert!(!app.execute_line("patch fix.patch"));
+        assert_eq!(app.workspace.editor(base.join("a.txt")).unwrap().all_lines(), ["ONE"]);
+        assert_eq!(app.workspace.editor(base.join("b.txt")).unwrap().all_lines(), ["two"]);
+        // 活跃文件恢复成执行前的那个
+        assert_eq!(app.workspace.active_file_path(), Some(base.join("c.txt")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn history_retention_rejects_overflowing_age() -> AppResult<()> {
+        let (mut app, _events, _tmp) = new_test_app()?;
+        app.execute_line("history-retention age 30");
+        let before = app.workspace.history_retention().describe();
+
+        app.execute_line("history-retention age 300000000000000");
+        assert_eq!(app.workspace.history_retention().describe(), before);
+
+        Ok(())
+    }
 }
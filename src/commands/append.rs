@@ -32,10 +32,18 @@ impl DocCommand for AppendLineCommand {
 
 pub fn cmd_append(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
     let text = args
-        .get(0)
+        .first()
         .ok_or_else(|| crate::error::AppError::InvalidArgs("append <text>".into()))?;
 
-    let cmd = AppendLineCommand::new(text.clone());
+    // 开启自动缩进时沿用上一行的前导空白
+    let ed = app.workspace.active_editor()?;
+    let line = if ed.indent_config().auto {
+        format!("{}{}", ed.last_line_indent(), text)
+    } else {
+        text.clone()
+    };
+
+    let cmd = AppendLineCommand::new(line);
 
     app.workspace.exec_doc(Box::new(cmd))?;
 
@@ -0,0 +1,37 @@
+//! commands/backup.rs
+//!
+//! backup [off|tilde|timestamp]
+//! 设置保存覆盖已有文件前的备份方式，不带参数时显示当前设置。
+
+use crate::{
+    application::Application,
+    outcome::Outcome,
+    error::{AppError, AppResult},
+    persist::BackupMode,
+};
+use super::CommandDef;
+
+pub fn cmd_backup(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let mode = match args.first().map(|s| s.as_str()) {
+        None => {
+            let current = match app.workspace.backup_mode() {
+                BackupMode::Off => "off",
+                BackupMode::Tilde => "tilde",
+                BackupMode::Timestamp => "timestamp",
+            };
+            return Ok(Outcome::print(format!("backup: {}", current)));
+        }
+        Some("off") => BackupMode::Off,
+        Some("tilde") => BackupMode::Tilde,
+        Some("timestamp") => BackupMode::Timestamp,
+        Some(_) => return Err(AppError::InvalidArgs("backup [off|tilde|timestamp]".into())),
+    };
+
+    app.workspace.set_backup_mode(mode);
+    Ok(Outcome::log(format!("backup {}", args[0])))
+}
+
+pub const BACKUP_COMMAND: CommandDef = CommandDef {
+    name: "backup",
+    handler: cmd_backup,
+};
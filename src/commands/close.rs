@@ -28,7 +28,7 @@ pub fn cmd_close(app: &mut Application, _args: &[String]) -> AppResult<Outcome>
 
         let input = input.trim().to_lowercase();
         if input == "y" || input == "yes" {
-            app.workspace.save_file(&path_str)?;
+            app.workspace.save_file(&path_str, false)?;
             println!("Saving...");
         } else if input == "n" || input == "no" {
             println!("Not saving...");
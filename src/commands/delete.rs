@@ -1,5 +1,5 @@
 use crate::{
-    application::Application, commands::{doc_command::DocCommand, util}, editor::Editor, error::{AppError, AppResult}, outcome::Outcome
+    application::Application, commands::{doc_command::DocCommand, util}, editor::Editor, error::{AppError, AppResult}, outcome::Outcome, persist::Register
 };
 use super::CommandDef;
 
@@ -49,8 +49,11 @@ pub fn cmd_delete(app: &mut Application, args: &[String]) -> AppResult<Outcome>
         .parse::<usize>()
         .map_err(|_| AppError::InvalidArgs("len must be a number".into()))?;
 
+    // 删除前先取出内容，成功后放进 "- 寄存器
+    let deleted = app.workspace.active_editor()?.peek_text(line, col, len)?;
     let cmd = DeleteTextCommand::new(line, col, len);
     app.workspace.exec_doc(Box::new(cmd))?;
+    app.workspace.record_deleted(Register::Chars(deleted));
 
     Ok(Outcome {
         print: None,
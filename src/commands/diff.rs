@@ -0,0 +1,89 @@
+//! commands/diff.rs
+//!
+//! diff [-U <n>] [file] [other]
+//! - 无参数：活跃文件的磁盘内容 → 缓冲区
+//! - file：该文件的磁盘内容 → 缓冲区
+//! - file other：两个已打开文件的缓冲区之间比较
+//!
+//! -U / --context 指定上下文行数（默认 3）；stdout 是终端时输出带颜色。
+
+use std::io::IsTerminal;
+
+use crate::{
+    application::Application,
+    diff,
+    outcome::Outcome,
+    error::{AppError, AppResult},
+};
+use super::CommandDef;
+
+const USAGE: &str = "diff [-U <n>] [file] [other]";
+const DEFAULT_CONTEXT: usize = 3;
+
+/// 输出 diff 结果：没有差异时给出提示，终端上加颜色
+pub(super) fn render(out: String) -> Outcome {
+    if out.is_empty() {
+        return Outcome::print("no differences");
+    }
+    let out = if std::io::stdout().is_terminal() { diff::colorize(&out) } else { out };
+    Outcome::print(out.trim_end())
+}
+
+pub fn cmd_diff(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let mut context = DEFAULT_CONTEXT;
+    let mut files: Vec<&str> = Vec::new();
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "-U" | "--context" => {
+                let n = it.next().ok_or_else(|| AppError::InvalidArgs(USAGE.into()))?;
+                context = n
+                    .parse()
+                    .map_err(|_| AppError::InvalidArgs(format!("invalid context size: {}", n)))?;
+            }
+            s if s.starts_with("-U") => {
+                context = s[2..]
+                    .parse()
+                    .map_err(|_| AppError::InvalidArgs(format!("invalid context size: {}", &s[2..])))?;
+            }
+            s => files.push(s),
+        }
+    }
+
+    let ws = &app.workspace;
+    let not_open = |p: &std::path::Path| AppError::InvalidArgs(format!("file not open: {}", p.display()));
+    let out = match files.as_slice() {
+        [] | [_] => {
+            let path = match files.first() {
+                Some(f) => ws.resolve_path(Some(f)),
+                None => ws
+                    .active_file_path()
+                    .ok_or_else(|| AppError::InvalidArgs("no active file".into()))?,
+            };
+            let ed = ws.editor(&path).ok_or_else(|| not_open(&path))?;
+            let (disk, _) = ws.disk_lines(&path)?;
+            let label = path.display().to_string();
+            diff::unified_diff(&disk, ed.all_lines(), &label, &format!("{} (buffer)", label), context)
+        }
+        [a, b] => {
+            let (pa, pb) = (ws.resolve_path(Some(a)), ws.resolve_path(Some(b)));
+            let ea = ws.editor(&pa).ok_or_else(|| not_open(&pa))?;
+            let eb = ws.editor(&pb).ok_or_else(|| not_open(&pb))?;
+            diff::unified_diff(
+                ea.all_lines(),
+                eb.all_lines(),
+                &pa.display().to_string(),
+                &pb.display().to_string(),
+                context,
+            )
+        }
+        _ => return Err(AppError::InvalidArgs(USAGE.into())),
+    };
+
+    Ok(render(out))
+}
+
+pub const DIFF_COMMAND: CommandDef = CommandDef {
+    name: "diff",
+    handler: cmd_diff,
+};
@@ -1,15 +1,30 @@
 //! commands/dir_tree.rs
 //!
-//! dir-tree [path]
+//! dir-tree [path] [--depth <n>] [--include <glob>]... [--exclude <glob>]... [--dirs-only]
+//!          [--size] [--mtime] [--all] [--no-ignore]
 //! 以树状结构显示当前或指定目录的文件树。
+//! - 默认不显示以 `.` 开头的文件，--all 时显示；遵守各级目录里的 .gitignore，--no-ignore 时不看
+//! - --include 只保留匹配的文件（以及包含它们的目录），--exclude 去掉匹配的文件和目录；
+//!   不含 `/` 的模式匹配任意深度的名字，否则按相对于根目录的路径匹配
+//! - 符号链接只显示指向，不进入，避免绕回上级目录死循环
+//! - 已打开的文件标 [open]，有未保存修改的标 [modified]
 
 use std::{
     ffi::OsStr,
     fs,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
-use crate::{application::Application, outcome::Outcome, error::{AppError, AppResult}};
+use chrono::{DateTime, Local};
+
+use crate::{
+    application::Application,
+    glob::{Glob, IgnoreRules},
+    outcome::Outcome,
+    workspace::Workspace,
+    error::{AppError, AppResult},
+};
 use super::CommandDef;
 
 pub const DIR_TREE_COMMAND: CommandDef = CommandDef {
@@ -17,13 +32,63 @@ pub const DIR_TREE_COMMAND: CommandDef = CommandDef {
     handler: cmd_dir_tree,
 };
 
+const USAGE: &str = "dir-tree [path] [--depth <n>] [--include <glob>]... [--exclude <glob>]... \
+                     [--dirs-only] [--size] [--mtime] [--all] [--no-ignore]";
+
+#[derive(Default)]
+struct TreeOptions {
+    depth: Option<usize>,
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+    dirs_only: bool,
+    size: bool,
+    mtime: bool,
+    all: bool,
+    no_ignore: bool,
+}
+
+impl TreeOptions {
+    /// 返回选项和可选的路径参数
+    fn parse(args: &[String]) -> AppResult<(Self, Option<&str>)> {
+        let mut opts = Self::default();
+        let mut path = None;
+        let mut it = args.iter();
+        while let Some(arg) = it.next() {
+            let mut value = || it.next().ok_or_else(|| AppError::InvalidArgs(USAGE.into()));
+            match arg.as_str() {
+                "--depth" => {
+                    let v = value()?;
+                    opts.depth = Some(v.parse().map_err(|_| AppError::InvalidArgs(format!("invalid depth: {}", v)))?);
+                }
+                "--include" => opts.include.push(name_glob(value()?)?),
+                "--exclude" => opts.exclude.push(name_glob(value()?)?),
+                "--dirs-only" => opts.dirs_only = true,
+                "--size" => opts.size = true,
+                "--mtime" => opts.mtime = true,
+                "--all" | "-a" => opts.all = true,
+                "--no-ignore" => opts.no_ignore = true,
+                s if path.is_none() && !s.starts_with('-') => path = Some(s),
+                _ => return Err(AppError::InvalidArgs(USAGE.into())),
+            }
+        }
+        Ok((opts, path))
+    }
+}
+
+/// 不含 `/` 的模式匹配任意深度的名字，与 .gitignore 的写法一致
+fn name_glob(pattern: &str) -> AppResult<Glob> {
+    match pattern.strip_prefix('/') {
+        Some(anchored) => Glob::new(anchored),
+        None if pattern.contains('/') => Glob::new(pattern),
+        None => Glob::new(&format!("**/{}", pattern)),
+    }
+}
+
 pub fn cmd_dir_tree(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
-    // 解析路径
-    let root_path: PathBuf = {
-        let arg_opt = args.get(0).map(|s| s.as_str());
-        app.workspace.resolve_path(arg_opt)   
-    };
+    let (opts, arg) = TreeOptions::parse(args)?;
+    let root_path: PathBuf = app.workspace.resolve_path(arg);
 
+    app.workspace.check_path(&root_path)?;
     if !root_path.exists() {
         return Err(AppError::InvalidArgs(format!(
             "path does not exist: {}",
@@ -41,58 +106,159 @@ pub fn cmd_dir_tree(app: &mut Application, args: &[String]) -> AppResult<Outcome
         .to_string_lossy();
     out.push_str(&format!("{}\n", root_name));
 
-    build_tree(&root_path, "", &mut out).map_err(AppError::Io)?;
+    let mut ignores = Vec::new();
+    let nodes = collect(&root_path, "", 1, &opts, &mut ignores).map_err(AppError::Io)?;
+    render(&nodes, "", &opts, &app.workspace, &mut out);
 
     Ok(Outcome {
         print: Some(out),
-        log: Some(format!(
-            "dir-tree {}",
-            args.get(0).cloned().unwrap_or_else(|| ".".into())
-        )),
+        log: Some(format!("dir-tree {}", args.join(" "))),
         exit: false,
     })
 }
 
-/// 递归构建树形结构
-fn build_tree(path: &Path, prefix: &str, out: &mut String) -> std::io::Result<()> {
-    // 读取子项
-    let mut entries: Vec<_> = fs::read_dir(path)?
-        .filter_map(|e| e.ok())
-        .collect();
+enum Kind {
+    Dir,
+    File,
+    Link(PathBuf),
+}
+
+struct Node {
+    name: String,
+    path: PathBuf,
+    kind: Kind,
+    size: u64,
+    mtime: Option<SystemTime>,
+    children: Vec<Node>,
+}
 
-    // 排序：目录在前，文件在后，同类按名字排序
-    entries.sort_by(|a, b| {
-        let a_meta = a.metadata();
-        let b_meta = b.metadata();
-
-        let a_is_dir = a_meta.as_ref().map(|m| m.is_dir()).unwrap_or(false);
-        let b_is_dir = b_meta.as_ref().map(|m| m.is_dir()).unwrap_or(false);
-
-        match (a_is_dir, b_is_dir) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a
-                .file_name()
-                .to_string_lossy()
-                .cmp(&b.file_name().to_string_lossy()),
+/// 读取一层目录。rel 是相对于根目录的路径（根目录为空串），level 是子项所在的层数（从 1 起）。
+/// ignores 是从根目录到这里各级 .gitignore 的规则，连同所在目录的相对路径。
+fn collect(
+    dir: &Path,
+    rel: &str,
+    level: usize,
+    opts: &TreeOptions,
+    ignores: &mut Vec<(String, IgnoreRules)>,
+) -> std::io::Result<Vec<Node>> {
+    let entries = fs::read_dir(dir)?;
+    let pushed = !opts.no_ignore
+        && match fs::read_to_string(dir.join(".gitignore")) {
+            Ok(text) => {
+                ignores.push((rel.to_string(), IgnoreRules::parse(&text)));
+                true
+            }
+            Err(_) => false,
+        };
+
+    let mut nodes = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !opts.all && name.starts_with('.') {
+            continue;
+        }
+        let child_rel = if rel.is_empty() { name.clone() } else { format!("{}/{}", rel, name) };
+        let path = entry.path();
+        // symlink_metadata 不跟随符号链接
+        let Ok(meta) = fs::symlink_metadata(&path) else { continue };
+        let kind = if meta.is_symlink() {
+            Kind::Link(fs::read_link(&path).unwrap_or_default())
+        } else if meta.is_dir() {
+            Kind::Dir
+        } else {
+            Kind::File
+        };
+        let is_dir = matches!(kind, Kind::Dir);
+
+        if is_ignored(ignores, &child_rel, is_dir)
+            || opts.exclude.iter().any(|g| g.matches(&child_rel))
+            || (opts.dirs_only && !is_dir)
+            || (!is_dir && !opts.include.is_empty() && !opts.include.iter().any(|g| g.matches(&child_rel)))
+        {
+            continue;
         }
+
+        let descend = is_dir && opts.depth.is_none_or(|d| level < d);
+        let children = if descend {
+            // 读不了的子目录当作空目录显示
+            collect(&path, &child_rel, level + 1, opts, ignores).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        // 有 --include 时，展开后没有匹配文件的目录不显示
+        if descend && children.is_empty() && !opts.include.is_empty() && !opts.dirs_only {
+            continue;
+        }
+
+        nodes.push(Node {
+            name,
+            path,
+            kind,
+            size: meta.len(),
+            mtime: meta.modified().ok(),
+            children,
+        });
+    }
+    if pushed {
+        ignores.pop();
+    }
+
+    // 排序：目录在前，文件在后，同类按名字排序
+    nodes.sort_by(|a, b| {
+        let (a_dir, b_dir) = (matches!(a.kind, Kind::Dir), matches!(b.kind, Kind::Dir));
+        b_dir.cmp(&a_dir).then_with(|| a.name.cmp(&b.name))
     });
+    Ok(nodes)
+}
 
-    let count = entries.len();
+/// 从外到内检查各级 .gitignore，里层的规则优先
+fn is_ignored(ignores: &[(String, IgnoreRules)], rel: &str, is_dir: bool) -> bool {
+    let mut ignored = false;
+    for (base, rules) in ignores {
+        let sub = if base.is_empty() {
+            rel
+        } else {
+            match rel.strip_prefix(base.as_str()).and_then(|r| r.strip_prefix('/')) {
+                Some(r) => r,
+                None => continue,
+            }
+        };
+        if let Some(v) = rules.check(sub, is_dir) {
+            ignored = v;
+        }
+    }
+    ignored
+}
 
-    for (idx, entry) in entries.into_iter().enumerate() {
+fn render(nodes: &[Node], prefix: &str, opts: &TreeOptions, ws: &Workspace, out: &mut String) {
+    let count = nodes.len();
+    for (idx, node) in nodes.iter().enumerate() {
         let is_last = idx + 1 == count;
 
         let connector = if is_last { "└── " } else { "├── " };
-        let name = entry.file_name().to_string_lossy().into_owned();
-
         out.push_str(prefix);
         out.push_str(connector);
-        out.push_str(&name);
+        out.push_str(&node.name);
+
+        if let Kind::Link(target) = &node.kind {
+            out.push_str(&format!(" -> {}", target.display()));
+        }
+        let mut info = Vec::new();
+        if opts.size && matches!(node.kind, Kind::File) {
+            info.push(format!("{} B", node.size));
+        }
+        if opts.mtime && let Some(t) = node.mtime {
+            info.push(DateTime::<Local>::from(t).format("%Y-%m-%d %H:%M").to_string());
+        }
+        if !info.is_empty() {
+            out.push_str(&format!(" ({})", info.join(", ")));
+        }
+        if let Some(ed) = ws.editor(&node.path) {
+            out.push_str(if ed.is_modified() { " [modified]" } else { " [open]" });
+        }
         out.push('\n');
 
-        let file_type = entry.file_type()?;
-        if file_type.is_dir() {
+        if !node.children.is_empty() {
             // 子目录前缀：最后一个用 "    "，中间用 "│   "
             let mut new_prefix = String::from(prefix);
             if is_last {
@@ -100,9 +266,7 @@ fn build_tree(path: &Path, prefix: &str, out: &mut String) -> std::io::Result<()
             } else {
                 new_prefix.push_str("│   ");
             }
-            build_tree(&entry.path(), &new_prefix, out)?;
+            render(&node.children, &new_prefix, opts, ws, out);
         }
     }
-
-    Ok(())
 }
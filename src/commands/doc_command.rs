@@ -7,3 +7,71 @@ pub trait DocCommand {
     fn execute(&mut self, ed: &mut Editor) -> AppResult<()>;
     fn undo(&mut self, ed: &mut Editor) -> AppResult<()>;
 }
+
+/// 通用的整行替换：把从 `start` 起的 `count` 行换成 `new_lines`。
+/// 执行时记下被替换的原始行，撤销时原样放回；供各种按行范围改写的命令复用。
+pub struct ReplaceLinesCommand {
+    start: usize,
+    count: usize,
+    old_lines: Vec<String>,
+    new_lines: Vec<String>,
+}
+
+impl ReplaceLinesCommand {
+    pub fn new(start: usize, count: usize, new_lines: Vec<String>) -> Self {
+        Self {
+            start,
+            count,
+            old_lines: Vec::new(),
+            new_lines,
+        }
+    }
+}
+
+impl DocCommand for ReplaceLinesCommand {
+    fn execute(&mut self, ed: &mut Editor) -> AppResult<()> {
+        self.old_lines = ed.splice_lines(self.start, self.count, self.new_lines.clone())?;
+        Ok(())
+    }
+
+    fn undo(&mut self, ed: &mut Editor) -> AppResult<()> {
+        ed.splice_lines(self.start, self.new_lines.len(), self.old_lines.clone())?;
+        Ok(())
+    }
+}
+
+/// 整体替换全部内容（含结尾换行标记），用于保存前整理、重新加载等
+pub struct ReplaceContentCommand {
+    new_lines: Vec<String>,
+    final_newline: bool,
+    old_lines: Vec<String>,
+    old_final_newline: bool,
+}
+
+impl ReplaceContentCommand {
+    pub fn new(new_lines: Vec<String>, final_newline: bool) -> Self {
+        Self {
+            new_lines,
+            final_newline,
+            old_lines: Vec::new(),
+            old_final_newline: false,
+        }
+    }
+}
+
+impl DocCommand for ReplaceContentCommand {
+    fn execute(&mut self, ed: &mut Editor) -> AppResult<()> {
+        let n = ed.count_lines();
+        self.old_lines = ed.splice_lines(1, n, self.new_lines.clone())?;
+        self.old_final_newline = ed.has_final_newline();
+        ed.set_final_newline(self.final_newline);
+        Ok(())
+    }
+
+    fn undo(&mut self, ed: &mut Editor) -> AppResult<()> {
+        let n = ed.count_lines();
+        ed.splice_lines(1, n, self.old_lines.clone())?;
+        ed.set_final_newline(self.old_final_newline);
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@ use std::path::PathBuf;
 use super::CommandDef;
 
 pub fn cmd_edit(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
-    let raw_arg: String = args.get(0).cloned().unwrap_or_else(|| ".".into());
+    let raw_arg: String = args.first().cloned().unwrap_or_else(|| ".".into());
     let path: PathBuf = app.workspace.resolve_path(Some(raw_arg.as_str()));
 
     app.workspace.edit(&path)?;
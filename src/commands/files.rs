@@ -0,0 +1,158 @@
+//! commands/files.rs
+//!
+//! 不离开编辑器管理 base_dir 下的文件：
+//! - mkdir [-p] <dir>...                 新建目录，-p 时连同上级目录
+//! - rm [-r] <path>...                   移进回收站（目录需要 -r），其下已打开的文件随之关闭
+//! - mv [--force] <from> <to>            移动 / 改名，已打开的文件跟着改路径
+//! - cp [-r] [--force] <from> <to>       复制（目录需要 -r）
+//! - trash                               列出回收站，最新的在前
+//! - untrash <id>                        把回收站条目放回原处
+//!
+//! 路径都按 base_dir 解析。涉及有未保存修改的已打开文件时拒绝执行；
+//! 目标已存在时 mv / cp 需要 --force。
+
+use chrono::{DateTime, Local};
+
+use crate::{application::Application, outcome::Outcome, error::{AppError, AppResult}};
+use super::CommandDef;
+
+/// 把参数分成开关和路径；出现 allowed 以外的开关时报错
+fn split_flags<'a>(args: &'a [String], allowed: &[&str], usage: &str) -> AppResult<(Vec<&'a str>, Vec<&'a str>)> {
+    let (flags, paths): (Vec<&str>, Vec<&str>) = args
+        .iter()
+        .map(|s| s.as_str())
+        .partition(|a| a.starts_with('-') && a.len() > 1);
+    if flags.iter().any(|f| !allowed.contains(f)) {
+        return Err(AppError::InvalidArgs(usage.into()));
+    }
+    Ok((flags, paths))
+}
+
+pub fn cmd_mkdir(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    const USAGE: &str = "mkdir [-p] <dir>...";
+    let (flags, dirs) = split_flags(args, &["-p"], USAGE)?;
+    if dirs.is_empty() {
+        return Err(AppError::InvalidArgs(USAGE.into()));
+    }
+    for dir in &dirs {
+        let path = app.workspace.resolve_path(Some(dir));
+        app.workspace.make_dir(&path, flags.contains(&"-p"))?;
+    }
+    Ok(Outcome::log(format!("mkdir {}", args.join(" "))))
+}
+
+pub const MKDIR_COMMAND: CommandDef = CommandDef {
+    name: "mkdir",
+    handler: cmd_mkdir,
+};
+
+pub fn cmd_rm(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    const USAGE: &str = "rm [-r] <path>...";
+    let (flags, paths) = split_flags(args, &["-r"], USAGE)?;
+    if paths.is_empty() {
+        return Err(AppError::InvalidArgs(USAGE.into()));
+    }
+    let mut removed = Vec::new();
+    for raw in &paths {
+        let path = app.workspace.resolve_path(Some(raw));
+        let id = app.workspace.remove_path(&path, flags.contains(&"-r"))?;
+        removed.push(format!("{} -> trash {}", raw, id));
+    }
+    Ok(Outcome {
+        print: Some(removed.join("\n")),
+        log: Some(format!("rm {}", args.join(" "))),
+        exit: false,
+    })
+}
+
+pub const RM_COMMAND: CommandDef = CommandDef {
+    name: "rm",
+    handler: cmd_rm,
+};
+
+pub fn cmd_mv(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    const USAGE: &str = "mv [--force] <from> <to>";
+    let (flags, paths) = split_flags(args, &["--force"], USAGE)?;
+    let [from, to] = paths.as_slice() else {
+        return Err(AppError::InvalidArgs(USAGE.into()));
+    };
+    let from_path = app.workspace.resolve_path(Some(from));
+    let to_path = app.workspace.resolve_path(Some(to));
+    let dest = app.workspace.move_path(&from_path, &to_path, !flags.is_empty())?;
+    Ok(Outcome {
+        print: Some(format!("moved {} -> {}", from, dest.display())),
+        log: Some(format!("mv {}", args.join(" "))),
+        exit: false,
+    })
+}
+
+pub const MV_COMMAND: CommandDef = CommandDef {
+    name: "mv",
+    handler: cmd_mv,
+};
+
+pub fn cmd_cp(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    const USAGE: &str = "cp [-r] [--force] <from> <to>";
+    let (flags, paths) = split_flags(args, &["-r", "--force"], USAGE)?;
+    let [from, to] = paths.as_slice() else {
+        return Err(AppError::InvalidArgs(USAGE.into()));
+    };
+    let from_path = app.workspace.resolve_path(Some(from));
+    let to_path = app.workspace.resolve_path(Some(to));
+    let dest = app.workspace.copy_path(
+        &from_path,
+        &to_path,
+        flags.contains(&"--force"),
+        flags.contains(&"-r"),
+    )?;
+    Ok(Outcome {
+        print: Some(format!("copied {} -> {}", from, dest.display())),
+        log: Some(format!("cp {}", args.join(" "))),
+        exit: false,
+    })
+}
+
+pub const CP_COMMAND: CommandDef = CommandDef {
+    name: "cp",
+    handler: cmd_cp,
+};
+
+pub fn cmd_trash(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    if !args.is_empty() {
+        return Err(AppError::InvalidArgs("trash".into()));
+    }
+    let entries = app.workspace.trash_list()?;
+    if entries.is_empty() {
+        return Ok(Outcome::print("trash is empty"));
+    }
+    let list: Vec<String> = entries
+        .iter()
+        .map(|e| {
+            let time = DateTime::<Local>::from(e.deleted_at).format("%Y-%m-%d %H:%M:%S");
+            format!("{}  {}  {}", e.id, time, e.origin.display())
+        })
+        .collect();
+    Ok(Outcome::print(list.join("\n")))
+}
+
+pub const TRASH_COMMAND: CommandDef = CommandDef {
+    name: "trash",
+    handler: cmd_trash,
+};
+
+pub fn cmd_untrash(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let [id] = args else {
+        return Err(AppError::InvalidArgs("untrash <id>".into()));
+    };
+    let origin = app.workspace.restore_trash(id)?;
+    Ok(Outcome {
+        print: Some(format!("restored {}", origin.display())),
+        log: Some(format!("untrash {}", id)),
+        exit: false,
+    })
+}
+
+pub const UNTRASH_COMMAND: CommandDef = CommandDef {
+    name: "untrash",
+    handler: cmd_untrash,
+};
@@ -0,0 +1,176 @@
+//! commands/filter.rs
+//!
+//! filter <range> <shell command>
+//! 把选中的行通过 stdin 交给外部程序，用其 stdout 替换原范围（类似 vi 的 `!`）。
+//! 外部程序不受路径沙箱约束，沙箱开启时不允许使用。
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+};
+
+use crate::{
+    application::Application,
+    outcome::Outcome,
+    error::{AppError, AppResult},
+    commands::{doc_command::ReplaceLinesCommand, util},
+};
+use super::CommandDef;
+
+pub fn cmd_filter(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    if args.len() < 2 {
+        return Err(AppError::InvalidArgs(
+            "filter <range> <shell command>".into(),
+        ));
+    }
+
+    if app.workspace.sandbox().is_some() {
+        return Err(AppError::OutsideSandbox("filter runs shell commands, disabled in sandbox mode".into()));
+    }
+
+    let (s, e) = util::active_range(app, &args[0])?;
+    // 单个参数视为用户已加引号的完整命令行；多个参数重新转义后拼接
+    let shell_cmd = if args.len() == 2 {
+        args[1].clone()
+    } else {
+        shell_words::join(&args[1..])
+    };
+
+    let input = app.workspace.active_editor()?.lines_range(s, e)?;
+
+    // 外部程序出错时直接返回，缓冲区保持不变
+    let output = run_filter(&shell_cmd, &input)?;
+
+    let cmd = ReplaceLinesCommand::new(s, e - s + 1, output);
+    app.workspace.exec_doc(Box::new(cmd))?;
+
+    Ok(Outcome {
+        print: None,
+        log: Some(format!("filter {}", shell_words::join([args[0].as_str(), shell_cmd.as_str()]))),
+        exit: false,
+    })
+}
+
+pub const FILTER_COMMAND: CommandDef = CommandDef {
+    name: "filter",
+    handler: cmd_filter,
+};
+
+/// 运行外部命令：输入各行写入 stdin，按行返回 stdout。
+fn run_filter(shell_cmd: &str, input: &[String]) -> AppResult<Vec<String>> {
+    let mut child = shell(shell_cmd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::ExternalCommand(format!("{}: {}", shell_cmd, e)))?;
+
+    // 单独线程写 stdin，避免输出较多时双方互相等待管道
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| AppError::InternalError("failed to open child stdin".into()))?;
+    let mut data = input.join("\n");
+    data.push('\n');
+    let writer = thread::spawn(move || {
+        // 子进程可能不读 stdin 就退出（如 `echo`），此时的 BrokenPipe 不算错误
+        let _ = stdin.write_all(data.as_bytes());
+    });
+
+    let output = child.wait_with_output()?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let mut msg = format!("{} exited with {}", shell_cmd, output.status);
+        if !stderr.trim().is_empty() {
+            msg.push_str(": ");
+            msg.push_str(stderr.trim());
+        }
+        return Err(AppError::ExternalCommand(msg));
+    }
+
+    let stdout = String::from_utf8(output.stdout).map_err(|_| {
+        AppError::ExternalCommand(format!("{}: output is not valid UTF-8", shell_cmd))
+    })?;
+    Ok(stdout.lines().map(|s| s.to_string()).collect())
+}
+
+#[cfg(windows)]
+fn shell(cmd: &str) -> Command {
+    let mut c = Command::new("cmd");
+    c.args(["/C", cmd]);
+    c
+}
+
+#[cfg(not(windows))]
+fn shell(cmd: &str) -> Command {
+    let mut c = Command::new("sh");
+    c.args(["-c", cmd]);
+    c
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        sandbox::Sandbox,
+        test_util::{app_with, strings},
+        workspace::Workspace,
+    };
+
+    fn new_app(tmp: &tempfile::TempDir) -> Application {
+        let mut workspace = Workspace::new(tmp.path().join("work_dir"));
+        let path = workspace.resolve_path(Some("a.txt"));
+        std::fs::write(&path, "c\nb\na\n").unwrap();
+        workspace.load(&path).unwrap();
+        app_with(workspace)
+    }
+
+    #[test]
+    fn filter_replaces_range_and_undo_restores_it() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut app = new_app(&tmp);
+        let out = cmd_filter(&mut app, &strings(&["1:2", "sort"])).unwrap();
+        assert_eq!(app.workspace.active_editor().unwrap().all_lines(), ["b", "c", "a"]);
+        assert_eq!(out.log.as_deref(), Some("filter 1:2 sort"));
+
+        app.workspace.undo().unwrap();
+        assert_eq!(app.workspace.active_editor().unwrap().all_lines(), ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn filter_log_quotes_the_command_for_replay() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut app = new_app(&tmp);
+        let cmd = r#"grep -v "b" | tr 'a-z' 'A-Z'"#;
+        let out = cmd_filter(&mut app, &strings(&["1:", cmd])).unwrap();
+        assert_eq!(app.workspace.active_editor().unwrap().all_lines(), ["C", "A"]);
+        assert_eq!(shell_words::split(&out.log.unwrap()).unwrap(), ["filter", "1:", cmd]);
+    }
+
+    #[test]
+    fn failing_command_leaves_buffer_untouched() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut app = new_app(&tmp);
+        let err = cmd_filter(&mut app, &strings(&["1:", "echo oops >&2; exit 3"]))
+            .err()
+            .expect("filter should fail");
+        assert_eq!(err.code(), 2003);
+        assert!(err.to_string().contains("oops"));
+        assert_eq!(app.workspace.active_editor().unwrap().all_lines(), ["c", "b", "a"]);
+        assert_eq!(app.workspace.active_modified(), Some(false));
+    }
+
+    #[test]
+    fn filter_is_refused_in_sandbox_mode() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut app = new_app(&tmp);
+        let sandbox = Sandbox::new(&app.workspace.get_base_dir(), &[]).unwrap();
+        app.workspace.set_sandbox(Some(sandbox));
+        let err = cmd_filter(&mut app, &strings(&["1:", "cat /etc/passwd"])).err().expect("filter should be refused");
+        assert_eq!(err.code(), 2007);
+        assert_eq!(app.workspace.active_editor().unwrap().all_lines(), ["c", "b", "a"]);
+    }
+}
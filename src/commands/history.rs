@@ -0,0 +1,139 @@
+//! commands/history.rs
+//!
+//! 本地历史（每次保存自动记录快照）：
+//! - history [file]                     列出快照，序号 1 为最新
+//! - history-diff <id> [file]           快照 → 当前缓冲区的差异
+//! - history-restore <id> [file]        用快照替换缓冲区（可 undo）
+//! - history-retention [count <n>|off] [age <days>|off]   查看 / 设置保留策略
+//!
+//! id 可以是快照时间戳，也可以是 history 列出的序号；file 省略时为活跃文件。
+
+use std::{path::PathBuf, time::Duration};
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    application::Application,
+    diff,
+    outcome::Outcome,
+    error::{AppError, AppResult},
+};
+use super::CommandDef;
+
+fn target(app: &Application, file: Option<&String>) -> AppResult<PathBuf> {
+    match file {
+        Some(f) => Ok(app.workspace.resolve_path(Some(f))),
+        None => app
+            .workspace
+            .active_file_path()
+            .ok_or_else(|| AppError::InvalidArgs("no active file".into())),
+    }
+}
+
+pub fn cmd_history(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    if args.len() > 1 {
+        return Err(AppError::InvalidArgs("history [file]".into()));
+    }
+    let path = target(app, args.first())?;
+    let snaps = app.workspace.history_list(&path)?;
+    if snaps.is_empty() {
+        return Ok(Outcome::print(format!("no history for {}", path.display())));
+    }
+
+    let list: Vec<String> = snaps
+        .iter()
+        .enumerate()
+        .map(|(n, s)| {
+            let time = DateTime::<Local>::from(s.saved_at).format("%Y-%m-%d %H:%M:%S");
+            format!("{:>3}  {}  {}  {} B", n + 1, s.id, time, s.size)
+        })
+        .collect();
+    Ok(Outcome::print(list.join("\n")))
+}
+
+pub const HISTORY_COMMAND: CommandDef = CommandDef {
+    name: "history",
+    handler: cmd_history,
+};
+
+pub fn cmd_history_diff(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let (Some(id), 1..=2) = (args.first(), args.len()) else {
+        return Err(AppError::InvalidArgs("history-diff <id> [file]".into()));
+    };
+    let path = target(app, args.get(1))?;
+    let ed = app
+        .workspace
+        .editor(&path)
+        .ok_or_else(|| AppError::InvalidArgs(format!("file not open: {}", path.display())))?;
+    let (old, _) = app.workspace.history_lines(&path, id)?;
+
+    let label = path.display().to_string();
+    let out = diff::unified_diff(&old, ed.all_lines(), &format!("{} ({})", label, id), &format!("{} (buffer)", label), 3);
+    Ok(super::diff::render(out))
+}
+
+pub const HISTORY_DIFF_COMMAND: CommandDef = CommandDef {
+    name: "history-diff",
+    handler: cmd_history_diff,
+};
+
+pub fn cmd_history_restore(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let (Some(id), 1..=2) = (args.first(), args.len()) else {
+        return Err(AppError::InvalidArgs("history-restore <id> [file]".into()));
+    };
+    let path = target(app, args.get(1))?;
+    app.workspace.restore_history(&path, id)?;
+
+    Ok(Outcome {
+        print: Some(format!("restored {} from {}", path.display(), id)),
+        log: Some(format!("history-restore {}", args.join(" "))),
+        exit: false,
+    })
+}
+
+pub const HISTORY_RESTORE_COMMAND: CommandDef = CommandDef {
+    name: "history-restore",
+    handler: cmd_history_restore,
+};
+
+pub fn cmd_history_retention(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    const USAGE: &str = "history-retention [count <n>|off] [age <days>|off]";
+    let mut retention = app.workspace.history_retention();
+    if args.is_empty() {
+        return Ok(Outcome::print(retention.describe()));
+    }
+    if !args.len().is_multiple_of(2) {
+        return Err(AppError::InvalidArgs(USAGE.into()));
+    }
+
+    for pair in args.chunks(2) {
+        let value: Option<u64> = match pair[1].as_str() {
+            "off" => None,
+            v => Some(v.parse().map_err(|_| AppError::InvalidArgs(format!("invalid number: {}", v)))?),
+        };
+        match pair[0].as_str() {
+            "count" => retention.max_count = value.map(|n| n as usize),
+            "age" => {
+                retention.max_age = value
+                    .map(|days| {
+                        days.checked_mul(86_400)
+                            .map(Duration::from_secs)
+                            .ok_or_else(|| AppError::InvalidArgs(format!("age too large: {} days", days)))
+                    })
+                    .transpose()?
+            }
+            _ => return Err(AppError::InvalidArgs(USAGE.into())),
+        }
+    }
+    if retention.max_count == Some(0) {
+        return Err(AppError::InvalidArgs("count must be at least 1".into()));
+    }
+    app.workspace.set_history_retention(retention);
+
+    Ok(Outcome::log(format!("history-retention {}", args.join(" "))))
+}
+
+pub const HISTORY_RETENTION_COMMAND: CommandDef = CommandDef {
+    name: "history-retention",
+    handler: cmd_history_retention,
+};
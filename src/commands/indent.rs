@@ -0,0 +1,198 @@
+//! commands/indent.rs
+//!
+//! 缩进相关命令，修改类命令都通过 ReplaceLinesCommand 整体撤销：
+//! - indent <range> [n]：增加 n 级缩进（默认 1），空行不处理
+//! - dedent <range> [n]：减少 n 级缩进，每级去掉一个制表符或至多 width 个空格
+//! - retab <range> spaces|tabs [width]：按列宽重写行首空白
+//! - indent-style spaces|tabs [width]：设置当前文件的缩进方式与宽度
+//! - auto-indent on|off：append 时是否沿用上一行的缩进
+
+use crate::{
+    application::Application,
+    outcome::Outcome,
+    error::{AppError, AppResult},
+    editor::{IndentConfig, IndentStyle},
+    commands::util,
+};
+use super::CommandDef;
+
+fn parse_count(arg: Option<&String>, what: &str) -> AppResult<usize> {
+    match arg {
+        None => Ok(1),
+        Some(s) => s
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| n >= 1)
+            .ok_or_else(|| AppError::InvalidArgs(format!("invalid {}: {}", what, s))),
+    }
+}
+
+fn parse_style(s: &str) -> AppResult<IndentStyle> {
+    match s {
+        "spaces" => Ok(IndentStyle::Spaces),
+        "tabs" => Ok(IndentStyle::Tabs),
+        other => Err(AppError::InvalidArgs(format!(
+            "indent style must be spaces or tabs, got {}",
+            other
+        ))),
+    }
+}
+
+fn split_indent(line: &str) -> (&str, &str) {
+    line.split_at(line.len() - line.trim_start_matches([' ', '\t']).len())
+}
+
+fn dedent_line(line: &str, levels: usize, width: usize) -> String {
+    let mut rest = line;
+    for _ in 0..levels {
+        if let Some(r) = rest.strip_prefix('\t') {
+            rest = r;
+        } else {
+            let spaces = rest.len() - rest.trim_start_matches(' ').len();
+            if spaces == 0 {
+                break;
+            }
+            rest = &rest[spaces.min(width)..];
+        }
+    }
+    rest.to_string()
+}
+
+/// 把行首空白换算成显示列宽，再按目标风格重新生成
+fn retab_line(line: &str, style: IndentStyle, width: usize) -> String {
+    let (indent, body) = split_indent(line);
+    let mut col = 0;
+    for c in indent.chars() {
+        col = if c == '\t' { (col / width + 1) * width } else { col + 1 };
+    }
+    let new_indent = match style {
+        IndentStyle::Spaces => " ".repeat(col),
+        IndentStyle::Tabs => format!("{}{}", "\t".repeat(col / width), " ".repeat(col % width)),
+    };
+    format!("{}{}", new_indent, body)
+}
+
+// ==== CLI ====
+
+pub fn cmd_indent(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let spec = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("indent <range> [n]".into()))?;
+    let n = parse_count(args.get(1), "indent level")?;
+
+    let prefix = app.workspace.active_editor()?.indent_config().unit().repeat(n);
+    util::rewrite_range(app, spec, |lines| {
+        lines
+            .into_iter()
+            .map(|l| if l.trim().is_empty() { l } else { format!("{}{}", prefix, l) })
+            .collect()
+    })?;
+
+    Ok(Outcome::log(format!("indent {} {}", spec, n)))
+}
+
+pub const INDENT_COMMAND: CommandDef = CommandDef {
+    name: "indent",
+    handler: cmd_indent,
+};
+
+pub fn cmd_dedent(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let spec = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("dedent <range> [n]".into()))?;
+    let n = parse_count(args.get(1), "indent level")?;
+
+    let width = app.workspace.active_editor()?.indent_config().width;
+    util::rewrite_range(app, spec, |lines| {
+        lines.iter().map(|l| dedent_line(l, n, width)).collect()
+    })?;
+
+    Ok(Outcome::log(format!("dedent {} {}", spec, n)))
+}
+
+pub const DEDENT_COMMAND: CommandDef = CommandDef {
+    name: "dedent",
+    handler: cmd_dedent,
+};
+
+pub fn cmd_retab(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    if args.len() < 2 {
+        return Err(AppError::InvalidArgs("retab <range> spaces|tabs [width]".into()));
+    }
+    let style = parse_style(&args[1])?;
+    let width = match args.get(2) {
+        Some(_) => parse_count(args.get(2), "tab width")?,
+        None => app.workspace.active_editor()?.indent_config().width,
+    };
+
+    util::rewrite_range(app, &args[0], |lines| {
+        lines.iter().map(|l| retab_line(l, style, width)).collect()
+    })?;
+
+    Ok(Outcome::log(format!("retab {} {} {}", args[0], args[1], width)))
+}
+
+pub const RETAB_COMMAND: CommandDef = CommandDef {
+    name: "retab",
+    handler: cmd_retab,
+};
+
+pub fn cmd_indent_style(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let style_arg = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("indent-style spaces|tabs [width]".into()))?;
+
+    let mut indent: IndentConfig = app.workspace.active_editor()?.indent_config();
+    indent.style = parse_style(style_arg)?;
+    if args.get(1).is_some() {
+        indent.width = parse_count(args.get(1), "tab width")?;
+    }
+    app.workspace.set_indent_config(indent)?;
+
+    Ok(Outcome::log(format!("indent-style {} {}", style_arg, indent.width)))
+}
+
+pub const INDENT_STYLE_COMMAND: CommandDef = CommandDef {
+    name: "indent-style",
+    handler: cmd_indent_style,
+};
+
+pub fn cmd_auto_indent(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let on = match args.first().map(|s| s.as_str()) {
+        Some("on") => true,
+        Some("off") => false,
+        _ => return Err(AppError::InvalidArgs("auto-indent on|off".into())),
+    };
+
+    let mut indent = app.workspace.active_editor()?.indent_config();
+    indent.auto = on;
+    app.workspace.set_indent_config(indent)?;
+
+    Ok(Outcome::log(format!("auto-indent {}", args[0])))
+}
+
+pub const AUTO_INDENT_COMMAND: CommandDef = CommandDef {
+    name: "auto-indent",
+    handler: cmd_auto_indent,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dedent_removes_tabs_or_width_spaces_per_level() {
+        assert_eq!(dedent_line("\t\tx", 1, 4), "\tx");
+        assert_eq!(dedent_line("      x", 1, 4), "  x");
+        assert_eq!(dedent_line("      x", 2, 4), "x");
+        assert_eq!(dedent_line("x", 3, 4), "x");
+    }
+
+    #[test]
+    fn retab_converts_leading_whitespace_by_column() {
+        assert_eq!(retab_line("\t  x\ty", IndentStyle::Spaces, 4), "      x\ty");
+        assert_eq!(retab_line("      x", IndentStyle::Tabs, 4), "\t  x");
+        // 制表符对齐到下一个 tab stop
+        assert_eq!(retab_line("  \tx", IndentStyle::Spaces, 4), "    x");
+    }
+}
@@ -3,7 +3,7 @@ use std::path::PathBuf;
 use super::CommandDef;
 
 pub fn cmd_init(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
-    let raw_arg: String = args.get(0).cloned().unwrap_or_else(|| ".".into());
+    let raw_arg: String = args.first().cloned().unwrap_or_else(|| ".".into());
     let path: PathBuf = app.workspace.resolve_path(Some(raw_arg.as_str()));
     let logging: bool = match args.get(1).map(|s| s.as_str()) {
         None => false,
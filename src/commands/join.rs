@@ -0,0 +1,119 @@
+//! commands/join.rs
+//!
+//! - join <range> [sep]：把范围内的行用 sep（默认一个空格）连接成一行
+//! - split <line:col>：在 (line, col) 处把一行断成两行
+
+use crate::{
+    application::Application,
+    outcome::Outcome,
+    error::{AppError, AppResult},
+    editor::Editor,
+    commands::{doc_command::DocCommand, util},
+};
+use super::CommandDef;
+
+// ==== DocCommand ====
+
+struct JoinLinesCommand {
+    start: usize,
+    count: usize,
+    sep: String,
+    original: Vec<String>,
+}
+
+impl JoinLinesCommand {
+    fn new(start: usize, count: usize, sep: String) -> Self {
+        Self { start, count, sep, original: Vec::new() }
+    }
+}
+
+impl DocCommand for JoinLinesCommand {
+    fn execute(&mut self, ed: &mut Editor) -> AppResult<()> {
+        let end = self.start + self.count - 1;
+        let joined = ed.lines_range(self.start, end)?.join(&self.sep);
+        self.original = ed.splice_lines(self.start, self.count, vec![joined])?;
+        Ok(())
+    }
+
+    fn undo(&mut self, ed: &mut Editor) -> AppResult<()> {
+        ed.splice_lines(self.start, 1, self.original.clone())?;
+        Ok(())
+    }
+}
+
+struct SplitLineCommand {
+    line: usize,
+    col: usize,
+}
+
+impl SplitLineCommand {
+    fn new(line: usize, col: usize) -> Self {
+        Self { line, col }
+    }
+}
+
+impl DocCommand for SplitLineCommand {
+    fn execute(&mut self, ed: &mut Editor) -> AppResult<()> {
+        let text = ed.lines_range(self.line, self.line)?.remove(0);
+        let len = text.len();
+        // 与 insert 一致：col 按字节计数，允许行尾后一位
+        if self.col == 0 || self.col > len + 1 || !text.is_char_boundary(self.col - 1) {
+            return Err(AppError::InvalidArgs(format!(
+                "column {} out of range for split on line length {} (valid 1..={})",
+                self.col,
+                len,
+                len + 1
+            )));
+        }
+        let (left, right) = text.split_at(self.col - 1);
+        ed.splice_lines(self.line, 1, vec![left.to_string(), right.to_string()])?;
+        Ok(())
+    }
+
+    fn undo(&mut self, ed: &mut Editor) -> AppResult<()> {
+        let parts = ed.splice_lines(self.line, 2, Vec::new())?;
+        ed.splice_lines(self.line, 0, vec![parts.concat()])?;
+        Ok(())
+    }
+}
+
+// ==== CLI ====
+
+pub fn cmd_join(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let spec = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("join <range> [sep]".into()))?;
+    let sep = args.get(1).cloned().unwrap_or_else(|| " ".into());
+
+    let (s, e) = util::active_range(app, spec)?;
+    if s == e {
+        return Err(AppError::InvalidArgs("join needs at least two lines".into()));
+    }
+
+    let cmd = JoinLinesCommand::new(s, e - s + 1, sep.clone());
+    app.workspace.exec_doc(Box::new(cmd))?;
+
+    Ok(Outcome::log(format!("join {} \"{}\"", spec, sep)))
+}
+
+pub const JOIN_COMMAND: CommandDef = CommandDef {
+    name: "join",
+    handler: cmd_join,
+};
+
+pub fn cmd_split(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let pos = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("split <line:col>".into()))?;
+
+    let (line, col) = util::parse_pos(pos)?;
+    let cmd = SplitLineCommand::new(line, col);
+    app.workspace.exec_doc(Box::new(cmd))?;
+
+    Ok(Outcome::log(format!("split {}", pos)))
+}
+
+pub const SPLIT_COMMAND: CommandDef = CommandDef {
+    name: "split",
+    handler: cmd_split,
+};
@@ -0,0 +1,173 @@
+//! commands/lines.rs
+//!
+//! 整行操作：
+//! - delete-lines <range>
+//! - move-lines <range> <dest>
+//! - copy-lines <range> <dest>
+//!
+//! `dest` 表示“放到第 dest 行之后”，0 表示文件开头（与 vi 的 `:m` / `:t` 一致）。
+
+use crate::{
+    application::Application,
+    outcome::Outcome,
+    error::{AppError, AppResult},
+    editor::Editor,
+    persist::Register,
+    commands::{doc_command::DocCommand, util},
+};
+use super::CommandDef;
+
+// ==== DocCommand ====
+
+struct DeleteLinesCommand {
+    start: usize,
+    count: usize,
+    deleted: Vec<String>,
+}
+
+impl DeleteLinesCommand {
+    fn new(start: usize, count: usize) -> Self {
+        Self { start, count, deleted: Vec::new() }
+    }
+}
+
+impl DocCommand for DeleteLinesCommand {
+    fn execute(&mut self, ed: &mut Editor) -> AppResult<()> {
+        self.deleted = ed.splice_lines(self.start, self.count, Vec::new())?;
+        Ok(())
+    }
+
+    fn undo(&mut self, ed: &mut Editor) -> AppResult<()> {
+        ed.splice_lines(self.start, 0, self.deleted.clone())?;
+        Ok(())
+    }
+}
+
+struct MoveLinesCommand {
+    start: usize,
+    count: usize,
+    dest: usize,
+    // 执行后这些行的新起点，撤销时从这里取回
+    moved_to: usize,
+}
+
+impl MoveLinesCommand {
+    fn new(start: usize, count: usize, dest: usize) -> Self {
+        Self { start, count, dest, moved_to: 0 }
+    }
+}
+
+impl DocCommand for MoveLinesCommand {
+    fn execute(&mut self, ed: &mut Editor) -> AppResult<()> {
+        let end = self.start + self.count - 1;
+        if self.dest > ed.count_lines() {
+            return Err(AppError::InvalidArgs(format!(
+                "destination {} out of range (0..={})",
+                self.dest,
+                ed.count_lines()
+            )));
+        }
+        if self.dest >= self.start && self.dest < end {
+            return Err(AppError::InvalidArgs(
+                "cannot move lines into themselves".into(),
+            ));
+        }
+
+        let lines = ed.splice_lines(self.start, self.count, Vec::new())?;
+        // 目标在范围之后时，删除后行号整体前移 count
+        self.moved_to = if self.dest >= end {
+            self.dest - self.count + 1
+        } else {
+            self.dest + 1
+        };
+        ed.splice_lines(self.moved_to, 0, lines)?;
+        Ok(())
+    }
+
+    fn undo(&mut self, ed: &mut Editor) -> AppResult<()> {
+        let lines = ed.splice_lines(self.moved_to, self.count, Vec::new())?;
+        ed.splice_lines(self.start, 0, lines)?;
+        Ok(())
+    }
+}
+
+struct CopyLinesCommand {
+    dest: usize,
+    lines: Vec<String>,
+}
+
+impl CopyLinesCommand {
+    fn new(dest: usize, lines: Vec<String>) -> Self {
+        Self { dest, lines }
+    }
+}
+
+impl DocCommand for CopyLinesCommand {
+    fn execute(&mut self, ed: &mut Editor) -> AppResult<()> {
+        ed.splice_lines(self.dest + 1, 0, self.lines.clone())?;
+        Ok(())
+    }
+
+    fn undo(&mut self, ed: &mut Editor) -> AppResult<()> {
+        ed.splice_lines(self.dest + 1, self.lines.len(), Vec::new())?;
+        Ok(())
+    }
+}
+
+// ==== CLI ====
+
+pub fn cmd_delete_lines(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let spec = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("delete-lines <range>".into()))?;
+
+    let (s, e) = util::active_range(app, spec)?;
+    let deleted = app.workspace.active_editor()?.lines_range(s, e)?;
+    let cmd = DeleteLinesCommand::new(s, e - s + 1);
+    app.workspace.exec_doc(Box::new(cmd))?;
+    app.workspace.record_deleted(Register::Lines(deleted));
+
+    Ok(Outcome::log(format!("delete-lines {}", spec)))
+}
+
+pub const DELETE_LINES_COMMAND: CommandDef = CommandDef {
+    name: "delete-lines",
+    handler: cmd_delete_lines,
+};
+
+pub fn cmd_move_lines(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    if args.len() < 2 {
+        return Err(AppError::InvalidArgs("move-lines <range> <dest>".into()));
+    }
+
+    let (s, e) = util::active_range(app, &args[0])?;
+    let dest = util::parse_dest(&args[1])?;
+    let cmd = MoveLinesCommand::new(s, e - s + 1, dest);
+    app.workspace.exec_doc(Box::new(cmd))?;
+
+    Ok(Outcome::log(format!("move-lines {} {}", args[0], dest)))
+}
+
+pub const MOVE_LINES_COMMAND: CommandDef = CommandDef {
+    name: "move-lines",
+    handler: cmd_move_lines,
+};
+
+pub fn cmd_copy_lines(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    if args.len() < 2 {
+        return Err(AppError::InvalidArgs("copy-lines <range> <dest>".into()));
+    }
+
+    let (s, e) = util::active_range(app, &args[0])?;
+    let dest = util::parse_dest(&args[1])?;
+    let lines = app.workspace.active_editor()?.lines_range(s, e)?;
+    let cmd = CopyLinesCommand::new(dest, lines);
+    app.workspace.exec_doc(Box::new(cmd))?;
+
+    Ok(Outcome::log(format!("copy-lines {} {}", args[0], dest)))
+}
+
+pub const COPY_LINES_COMMAND: CommandDef = CommandDef {
+    name: "copy-lines",
+    handler: cmd_copy_lines,
+};
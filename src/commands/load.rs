@@ -1,16 +1,123 @@
-use crate::{application::Application, outcome::Outcome, error::AppResult};
+//! commands/load.rs
+//!
+//! - load [--readonly] [--force] [--max <n>] [file|pattern]
+//!   打开文件。参数里有通配符（如 `src/**/*.rs`）时按 base_dir 展开，逐个打开匹配的文件：
+//!   匹配数超过上限（默认 100，--max 修改）时一个都不打开；
+//!   已打开的文件保持原样，不重新加载；
+//!   结束后汇总成功和失败的文件，活跃文件设为第一个打开成功的匹配。
+//!   二进制文件和超过大小上限的文件默认拒绝，--force 时照样打开。
+//! - max-file-size [<size>|off]   查看 / 设置打开文件的大小上限，size 可以带 K、M、G 后缀
+
+use std::fmt::Write;
+
+use crate::{application::Application, glob, outcome::Outcome, error::{AppError, AppResult}};
 use std::path::PathBuf;
 use super::CommandDef;
 
+const USAGE: &str = "load [--readonly] [--force] [--max <n>] [file|pattern]";
+/// 一次通配符加载最多打开的文件数
+const DEFAULT_MAX_FILES: usize = 100;
+
 pub fn cmd_load(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
-    let raw_arg: String = args.get(0).cloned().unwrap_or_else(|| ".".into());
+    let mut readonly = false;
+    let mut force = false;
+    let mut max = DEFAULT_MAX_FILES;
+    let mut file: Option<&str> = None;
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--readonly" => readonly = true,
+            "--force" => force = true,
+            "--max" => {
+                let v = it.next().ok_or_else(|| AppError::InvalidArgs(USAGE.into()))?;
+                max = v.parse().map_err(|_| AppError::InvalidArgs(format!("invalid number: {}", v)))?;
+            }
+            s if file.is_none() && !s.starts_with("--") => file = Some(s),
+            _ => return Err(AppError::InvalidArgs(USAGE.into())),
+        }
+    }
+    let raw_arg: String = file.unwrap_or(".").to_string();
+    if glob::has_wildcards(&raw_arg) {
+        return load_pattern(app, &raw_arg, (readonly, force), max, args);
+    }
     let path: PathBuf = app.workspace.resolve_path(Some(raw_arg.as_str()));
 
-    app.workspace.load(path)?;
+    // 每次 load 都按这次的参数重新设定只读标记
+    app.workspace.load_with(&path, force)?;
+    app.workspace.set_readonly(&path, readonly)?;
+    let ro = app.workspace.editor(&path).is_some_and(|ed| ed.is_readonly());
+
+    Ok(Outcome {
+        print: Some(format!("Loaded {}{}", raw_arg, if ro { " (read-only)" } else { "" })),
+        log: Some(format!("load {}", args.join(" "))),
+        exit: false,
+    })
+}
+
+/// flags 为 (readonly, force)
+fn load_pattern(
+    app: &mut Application,
+    pattern: &str,
+    (readonly, force): (bool, bool),
+    max: usize,
+    args: &[String],
+) -> AppResult<Outcome> {
+    let ws = &mut app.workspace;
+    // 沙箱外的目录不遍历；起点在沙箱内时，经符号链接指到外面的匹配也要去掉
+    let base = ws.get_base_dir();
+    ws.check_path(&glob::literal_root(&base, pattern))?;
+    let matches: Vec<PathBuf> = glob::expand(&base, pattern)?
+        .into_iter()
+        .filter(|p| ws.check_path(p).is_ok())
+        .collect();
+    if matches.is_empty() {
+        return Err(AppError::InvalidArgs(format!("no files match {}", pattern)));
+    }
+    if matches.len() > max {
+        return Err(AppError::InvalidArgs(format!(
+            "{} matches {} files, more than the limit of {} (use --max)",
+            pattern,
+            matches.len(),
+            max
+        )));
+    }
 
+    let prev_active = ws.active_file_path();
+    let mut first_loaded = None;
+    let mut failed = Vec::new();
+    let mut kept = Vec::new();
+    for path in &matches {
+        // 已打开的文件不重新加载，否则缓冲区里未保存的修改（连同交换文件）会被磁盘内容冲掉
+        if ws.editor(path).is_some() {
+            first_loaded.get_or_insert(path.clone());
+            kept.push(path);
+            continue;
+        }
+        let result = ws.load_with(path, force).and_then(|()| ws.set_readonly(path, readonly));
+        match result {
+            Ok(()) => {
+                first_loaded.get_or_insert(path.clone());
+            }
+            Err(e) => failed.push((path, e)),
+        }
+    }
+    // 全部失败时活跃文件保持原样
+    if let Some(first) = first_loaded.or(prev_active) {
+        ws.edit(first)?;
+    }
+
+    let mut report = format!("Loaded {} of {} file(s) matching {}", matches.len() - failed.len(), matches.len(), pattern);
+    for (path, e) in &failed {
+        let shown = path.strip_prefix(&base).unwrap_or(path);
+        let _ = write!(report, "\n  failed {}: {}", shown.display(), e);
+    }
+    for path in &kept {
+        let shown = path.strip_prefix(&base).unwrap_or(path);
+        let _ = write!(report, "\n  kept {}: already open", shown.display());
+    }
     Ok(Outcome {
-        print: Some(format!("Loaded {}", raw_arg)),
-        log: Some(format!("load {}", raw_arg)),
+        print: Some(report),
+        log: Some(format!("load {}", args.join(" "))),
         exit: false,
     })
 }
@@ -18,4 +125,109 @@ pub fn cmd_load(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
 pub const LOAD_COMMAND: CommandDef = CommandDef {
     name: "load",
     handler: cmd_load,
-};
\ No newline at end of file
+};
+
+/// 解析带 K / M / G 后缀（1024 进制）的大小
+fn parse_size(s: &str) -> Option<u64> {
+    let (num, unit) = match s.char_indices().last()? {
+        (i, 'K' | 'k') => (&s[..i], 1 << 10),
+        (i, 'M' | 'm') => (&s[..i], 1 << 20),
+        (i, 'G' | 'g') => (&s[..i], 1 << 30),
+        _ => (s, 1),
+    };
+    num.parse::<u64>().ok()?.checked_mul(unit)
+}
+
+pub fn cmd_max_file_size(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    match args {
+        [] => {
+            let shown = app
+                .workspace
+                .max_file_size()
+                .map_or("off".to_string(), |n| format!("{} bytes", n));
+            Ok(Outcome::print(format!("max file size: {}", shown)))
+        }
+        [v] if v == "off" => {
+            app.workspace.set_max_file_size(None);
+            Ok(Outcome::log("max-file-size off"))
+        }
+        [v] => {
+            let n = parse_size(v).ok_or_else(|| AppError::InvalidArgs(format!("invalid size: {}", v)))?;
+            app.workspace.set_max_file_size(Some(n));
+            Ok(Outcome::log(format!("max-file-size {}", v)))
+        }
+        _ => Err(AppError::InvalidArgs("max-file-size [<size>|off]".into())),
+    }
+}
+
+pub const MAX_FILE_SIZE_COMMAND: CommandDef = CommandDef {
+    name: "max-file-size",
+    handler: cmd_max_file_size,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::{
+        commands::doc_command::ReplaceContentCommand,
+        sandbox::Sandbox,
+        test_util::{app_with, strings},
+        workspace::Workspace,
+    };
+
+    #[test]
+    fn sizes_accept_binary_suffixes() {
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size("4K"), Some(4096));
+        assert_eq!(parse_size("10M"), Some(10 * 1024 * 1024));
+        assert_eq!(parse_size("1g"), Some(1 << 30));
+        assert_eq!(parse_size("M"), None);
+        assert_eq!(parse_size("-1"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn patterns_stay_inside_the_sandbox() {
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path().join("work_dir");
+        let outside = tmp.path().join("outside");
+        fs::create_dir_all(&base).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(base.join("a.txt"), "a\n").unwrap();
+        fs::write(outside.join("secret.txt"), "s\n").unwrap();
+        std::os::unix::fs::symlink(outside.join("secret.txt"), base.join("link.txt")).unwrap();
+
+        let mut workspace = Workspace::new(base.clone());
+        workspace.set_sandbox(Some(Sandbox::new(&base, &[]).unwrap()));
+        let mut app = app_with(workspace);
+
+        let err = cmd_load(&mut app, &strings(&["../outside/*.txt"])).err().expect("walk outside sandbox");
+        assert_eq!(err.code(), 2007);
+        // 指到沙箱外的符号链接不算匹配
+        let out = cmd_load(&mut app, &strings(&["*.txt"])).unwrap();
+        assert_eq!(out.print.as_deref(), Some("Loaded 1 of 1 file(s) matching *.txt"));
+        assert!(app.workspace.editor(base.join("link.txt")).is_none());
+    }
+
+    #[test]
+    fn patterns_keep_unsaved_changes_in_open_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path().join("work_dir");
+        fs::create_dir_all(&base).unwrap();
+        fs::write(base.join("a.txt"), "a\n").unwrap();
+        fs::write(base.join("b.txt"), "b\n").unwrap();
+        let mut workspace = Workspace::new(base.clone());
+        workspace.load(base.join("a.txt")).unwrap();
+        let edit = ReplaceContentCommand::new(strings(&["a", "edited"]), true);
+        workspace.exec_doc(Box::new(edit)).unwrap();
+        let mut app = app_with(workspace);
+
+        let out = cmd_load(&mut app, &strings(&["*.txt"])).unwrap();
+        assert!(out.print.unwrap().contains("kept a.txt: already open"));
+        let ed = app.workspace.editor(base.join("a.txt")).unwrap();
+        assert_eq!(ed.all_lines(), ["a", "edited"]);
+        assert!(ed.is_modified());
+        assert!(app.workspace.editor(base.join("b.txt")).is_some());
+    }
+}
@@ -8,7 +8,7 @@ use super::CommandDef;
 
 fn resolve_log_target(app: &Application, args: &[String]) -> AppResult<(PathBuf, String)> {
     // 有参数：按 base_dir 解析
-    if let Some(raw) = args.get(0) {
+    if let Some(raw) = args.first() {
         let path = app.workspace.resolve_path(Some(raw.as_str()));
         return Ok((path, raw.clone()));
     }
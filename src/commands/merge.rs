@@ -0,0 +1,133 @@
+//! commands/merge.rs
+//!
+//! - merge <base> <theirs>          以活跃文件为 ours 做三方合并，冲突处插入标记
+//! - conflicts                      列出活跃文件中的冲突块
+//! - resolve <n>|--all ours|theirs|both   按选择的一侧解决冲突
+//!
+//! base / theirs 已打开时取缓冲区内容，否则读磁盘。所有修改都可以 undo。
+
+use std::path::Path;
+
+use crate::{
+    application::Application,
+    commands::doc_command::{ReplaceContentCommand, ReplaceLinesCommand},
+    merge::{self, Side},
+    outcome::Outcome,
+    error::{AppError, AppResult},
+};
+use super::CommandDef;
+
+fn file_lines(app: &Application, path: &Path) -> AppResult<Vec<String>> {
+    match app.workspace.editor(path) {
+        Some(ed) => Ok(ed.all_lines().to_vec()),
+        None => Ok(app.workspace.disk_lines(path)?.0),
+    }
+}
+
+pub fn cmd_merge(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let [base, theirs] = args else {
+        return Err(AppError::InvalidArgs("merge <base> <theirs>".into()));
+    };
+    let base_lines = file_lines(app, &app.workspace.resolve_path(Some(base)))?;
+    let theirs_lines = file_lines(app, &app.workspace.resolve_path(Some(theirs)))?;
+
+    let ed = app.workspace.active_editor()?;
+    let ours_label = app
+        .workspace
+        .active_file_path()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "ours".into());
+    let result = merge::merge3(&base_lines, ed.all_lines(), &theirs_lines, &ours_label, theirs);
+    let final_newline = ed.has_final_newline();
+
+    if result.lines != ed.all_lines() {
+        app.workspace
+            .exec_doc(Box::new(ReplaceContentCommand::new(result.lines, final_newline)))?;
+    }
+
+    let msg = match result.conflicts {
+        0 => "merged cleanly".to_string(),
+        n => format!("merged with {} conflict(s); use `conflicts` and `resolve`", n),
+    };
+    Ok(Outcome {
+        print: Some(msg),
+        log: Some(format!("merge {} {}", base, theirs)),
+        exit: false,
+    })
+}
+
+pub const MERGE_COMMAND: CommandDef = CommandDef {
+    name: "merge",
+    handler: cmd_merge,
+};
+
+pub fn cmd_conflicts(app: &mut Application, _args: &[String]) -> AppResult<Outcome> {
+    let lines = app.workspace.active_editor()?.all_lines();
+    let conflicts = merge::find_conflicts(lines);
+    if conflicts.is_empty() {
+        return Ok(Outcome::print("no conflicts"));
+    }
+
+    let list: Vec<String> = conflicts
+        .iter()
+        .enumerate()
+        .map(|(n, c)| {
+            format!(
+                "#{} lines {}-{} (ours {} line(s), theirs {} line(s))",
+                n + 1,
+                c.start + 1,
+                c.end + 1,
+                c.ours(lines).len(),
+                c.theirs(lines).len()
+            )
+        })
+        .collect();
+    Ok(Outcome::print(list.join("\n")))
+}
+
+pub const CONFLICTS_COMMAND: CommandDef = CommandDef {
+    name: "conflicts",
+    handler: cmd_conflicts,
+};
+
+pub fn cmd_resolve(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    const USAGE: &str = "resolve <n>|--all ours|theirs|both";
+    let [which, side] = args else {
+        return Err(AppError::InvalidArgs(USAGE.into()));
+    };
+    let side = Side::parse(side).ok_or_else(|| AppError::InvalidArgs(USAGE.into()))?;
+
+    let ed = app.workspace.active_editor()?;
+    let lines = ed.all_lines();
+    let conflicts = merge::find_conflicts(lines);
+    if conflicts.is_empty() {
+        return Err(AppError::InvalidArgs("no conflicts".into()));
+    }
+
+    if which == "--all" {
+        // 从后往前替换，前面冲突块的行号不受影响；整体作为一条命令撤销
+        let mut new_lines = lines.to_vec();
+        for c in conflicts.iter().rev() {
+            new_lines.splice(c.start..=c.end, c.resolve(lines, side));
+        }
+        let cmd = ReplaceContentCommand::new(new_lines, ed.has_final_newline());
+        app.workspace.exec_doc(Box::new(cmd))?;
+    } else {
+        let n: usize = which
+            .parse()
+            .map_err(|_| AppError::InvalidArgs(USAGE.into()))?;
+        let c = n
+            .checked_sub(1)
+            .and_then(|i| conflicts.get(i))
+            .ok_or_else(|| AppError::InvalidArgs(format!("no conflict #{} ({} total)", n, conflicts.len())))?;
+        let cmd = ReplaceLinesCommand::new(c.start + 1, c.end - c.start + 1, c.resolve(lines, side));
+        app.workspace.exec_doc(Box::new(cmd))?;
+    }
+
+    Ok(Outcome::log(format!("resolve {}", args.join(" "))))
+}
+
+pub const RESOLVE_COMMAND: CommandDef = CommandDef {
+    name: "resolve",
+    handler: cmd_resolve,
+};
@@ -14,62 +14,185 @@ pub struct CommandDef {
 
 // 各命令模块
 mod append;
+mod backup;
 mod close;
 mod delete;
+mod diff;
 mod dir_tree;
+mod filter;
 mod load;
 mod log;
 mod show;
+mod sort;
 mod edit;
 mod editor_list;
 mod exit;
+mod files;
+mod history;
+mod indent;
 mod init;
 mod insert;
+mod join;
+mod lines;
+mod merge;
+mod patch;
+mod position;
+mod register;
 mod save;
+mod save_as;
+mod save_hook;
+mod session;
 mod undo;
+mod recover;
 mod redo;
+mod reload;
 mod replace;
 mod util;
+mod workspace_info;
 pub mod doc_command;
 
 // 导出子模块内部的 CommandDef 列表
 use append::APPEND_COMMAND;
+use backup::BACKUP_COMMAND;
 use close::CLOSE_COMMAND;
 use delete::DELETE_COMMAND;
+use diff::DIFF_COMMAND;
 use dir_tree::DIR_TREE_COMMAND;
+use filter::FILTER_COMMAND;
 use load::LOAD_COMMAND;
+use load::MAX_FILE_SIZE_COMMAND;
 use log::LOG_ON_COMMAND;
 use log::LOG_OFF_COMMAND;
 use log::LOG_SHOW_COMMAND;
 use show::SHOW_COMMAND;
+use sort::SORT_COMMAND;
+use sort::UNIQ_COMMAND;
+use sort::REVERSE_COMMAND;
+use sort::SHUFFLE_COMMAND;
 use edit::EDIT_COMMAND;
 use editor_list::LIST_COMMAND;
 use exit::EXIT_COMMAND;
+use files::MKDIR_COMMAND;
+use files::RM_COMMAND;
+use files::MV_COMMAND;
+use files::CP_COMMAND;
+use files::TRASH_COMMAND;
+use files::UNTRASH_COMMAND;
+use history::HISTORY_COMMAND;
+use history::HISTORY_DIFF_COMMAND;
+use history::HISTORY_RESTORE_COMMAND;
+use history::HISTORY_RETENTION_COMMAND;
+use indent::INDENT_COMMAND;
+use indent::DEDENT_COMMAND;
+use indent::RETAB_COMMAND;
+use indent::INDENT_STYLE_COMMAND;
+use indent::AUTO_INDENT_COMMAND;
 use init::INIT_COMMAND;
 use insert::INSERT_COMMAND;
+use join::JOIN_COMMAND;
+use join::SPLIT_COMMAND;
+use lines::DELETE_LINES_COMMAND;
+use lines::MOVE_LINES_COMMAND;
+use lines::COPY_LINES_COMMAND;
+use merge::MERGE_COMMAND;
+use merge::CONFLICTS_COMMAND;
+use merge::RESOLVE_COMMAND;
+use patch::PATCH_COMMAND;
+use position::GOTO_COMMAND;
+use position::WHERE_COMMAND;
+use position::BOOKMARK_COMMAND;
+use position::JUMP_COMMAND;
+use register::YANK_COMMAND;
+use register::PUT_COMMAND;
+use register::REGISTERS_COMMAND;
 use save::SAVE_COMMAND;
+use save_as::SAVE_AS_COMMAND;
+use save_as::RENAME_COMMAND;
+use save_as::DUPLICATE_COMMAND;
+use save_hook::SAVE_HOOK_COMMAND;
+use session::SESSION_SAVE_COMMAND;
+use session::SESSION_LOAD_COMMAND;
+use session::SESSION_LIST_COMMAND;
+use session::SESSION_DELETE_COMMAND;
 use undo::UNDO_COMMAND;
+use recover::RECOVER_COMMAND;
+use recover::DISCARD_COMMAND;
 use redo::REDO_COMMAND;
+use reload::RELOAD_COMMAND;
+use reload::DIFF_DISK_COMMAND;
 use replace::REPLACE_COMMAND;
+use workspace_info::WORKSPACE_INFO_COMMAND;
 
 /// 全局静态命令表
 pub static COMMANDS: &[CommandDef] = &[
     APPEND_COMMAND,
+    BACKUP_COMMAND,
     CLOSE_COMMAND,
     DELETE_COMMAND,
+    DIFF_COMMAND,
     DIR_TREE_COMMAND,
+    FILTER_COMMAND,
     LOAD_COMMAND,
+    MAX_FILE_SIZE_COMMAND,
     LOG_ON_COMMAND,
     LOG_OFF_COMMAND,
     LOG_SHOW_COMMAND,
     SHOW_COMMAND,
+    SORT_COMMAND,
+    UNIQ_COMMAND,
+    REVERSE_COMMAND,
+    SHUFFLE_COMMAND,
     EDIT_COMMAND,
     LIST_COMMAND,
     EXIT_COMMAND,
+    MKDIR_COMMAND,
+    RM_COMMAND,
+    MV_COMMAND,
+    CP_COMMAND,
+    TRASH_COMMAND,
+    UNTRASH_COMMAND,
+    HISTORY_COMMAND,
+    HISTORY_DIFF_COMMAND,
+    HISTORY_RESTORE_COMMAND,
+    HISTORY_RETENTION_COMMAND,
+    INDENT_COMMAND,
+    DEDENT_COMMAND,
+    RETAB_COMMAND,
+    INDENT_STYLE_COMMAND,
+    AUTO_INDENT_COMMAND,
     INIT_COMMAND,
     INSERT_COMMAND,
+    JOIN_COMMAND,
+    SPLIT_COMMAND,
+    DELETE_LINES_COMMAND,
+    MOVE_LINES_COMMAND,
+    COPY_LINES_COMMAND,
+    MERGE_COMMAND,
+    CONFLICTS_COMMAND,
+    RESOLVE_COMMAND,
+    PATCH_COMMAND,
+    GOTO_COMMAND,
+    WHERE_COMMAND,
+    BOOKMARK_COMMAND,
+    JUMP_COMMAND,
+    YANK_COMMAND,
+    PUT_COMMAND,
+    REGISTERS_COMMAND,
     SAVE_COMMAND,
+    SAVE_AS_COMMAND,
+    RENAME_COMMAND,
+    DUPLICATE_COMMAND,
+    SAVE_HOOK_COMMAND,
+    SESSION_SAVE_COMMAND,
+    SESSION_LOAD_COMMAND,
+    SESSION_LIST_COMMAND,
+    SESSION_DELETE_COMMAND,
     UNDO_COMMAND,
+    RECOVER_COMMAND,
+    DISCARD_COMMAND,
     REDO_COMMAND,
+    RELOAD_COMMAND,
+    DIFF_DISK_COMMAND,
     REPLACE_COMMAND,
+    WORKSPACE_INFO_COMMAND,
 ];
@@ -0,0 +1,145 @@
+//! commands/patch.rs
+//!
+//! patch [--dry-run] [-p <n>] [--fuzz <n>] <patchfile>
+//!
+//! 把 unified diff 应用到对应文件的缓冲区：未打开的文件自动打开，活跃文件保持不变。
+//! 每个文件的全部修改作为一条 DocCommand，可以单独 undo。
+//! 找不到位置的 hunk 逐个报告，其余 hunk 照常应用；
+//! 无法修改的文件（二进制、超大、沙箱外、只读）报告后跳过，其余文件照常处理。
+//! -p 去掉路径前缀的级数，默认对 a/ b/ 风格的路径去一级；--fuzz 默认 2。
+
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    application::Application,
+    commands::doc_command::ReplaceContentCommand,
+    outcome::Outcome,
+    patch::{self, FilePatch, HunkResult},
+    workspace::Workspace,
+    error::{AppError, AppResult},
+};
+use super::CommandDef;
+
+const USAGE: &str = "patch [--dry-run] [-p <n>] [--fuzz <n>] <patchfile>";
+const DEFAULT_FUZZ: usize = 2;
+
+fn parse_num(s: Option<&str>) -> AppResult<usize> {
+    let s = s.ok_or_else(|| AppError::InvalidArgs(USAGE.into()))?;
+    s.parse().map_err(|_| AppError::InvalidArgs(format!("invalid number: {}", s)))
+}
+
+pub fn cmd_patch(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let mut dry_run = false;
+    let mut strip: Option<usize> = None;
+    let mut fuzz = DEFAULT_FUZZ;
+    let mut patch_file: Option<&str> = None;
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "-p" => strip = Some(parse_num(it.next().map(|s| s.as_str()))?),
+            "--fuzz" => fuzz = parse_num(it.next().map(|s| s.as_str()))?,
+            s if s.starts_with("-p") && s.len() > 2 => strip = Some(parse_num(Some(&s[2..]))?),
+            s if patch_file.is_none() && !s.starts_with('-') => patch_file = Some(s),
+            _ => return Err(AppError::InvalidArgs(USAGE.into())),
+        }
+    }
+    let patch_file = patch_file.ok_or_else(|| AppError::InvalidArgs(USAGE.into()))?;
+
+    let patch_path = app.workspace.resolve_path(Some(patch_file));
+    app.workspace.check_path(&patch_path)?;
+    let text = fs::read_to_string(patch_path)?;
+    let files = patch::parse_patch(&text)?;
+
+    let ws = &mut app.workspace;
+    let prev_active = ws.active_file_path();
+    let mut first_loaded = None;
+    let mut report = String::new();
+    let mut total_rejected = 0;
+
+    let mut failed = 0;
+
+    for file in &files {
+        let path = ws.resolve_path(Some(&file.target(strip.unwrap_or_else(|| file.default_strip()))));
+        // 单个文件出错（二进制、超大、沙箱外、只读等）只报告，不影响其余文件
+        match patch_one(ws, &path, file, fuzz, dry_run, &mut first_loaded, &mut report) {
+            Ok(rejected) => total_rejected += rejected,
+            Err(e) => {
+                failed += 1;
+                let _ = writeln!(report, "failed {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    // load 会切换活跃文件，这里恢复；原来没有活跃文件时停在第一个新打开的文件上
+    if let Some(active) = prev_active.or(first_loaded) {
+        ws.edit(active)?;
+    }
+    if total_rejected > 0 {
+        let _ = writeln!(report, "{} hunk(s) rejected", total_rejected);
+    }
+    if failed > 0 {
+        let _ = writeln!(report, "{} file(s) failed", failed);
+    }
+
+    Ok(Outcome {
+        print: Some(report.trim_end().to_string()),
+        log: if dry_run { None } else { Some(format!("patch {}", args.join(" "))) },
+        exit: false,
+    })
+}
+
+/// 应用一个文件的补丁并写报告，返回被拒绝的 hunk 数
+fn patch_one(
+    ws: &mut Workspace,
+    path: &Path,
+    file: &FilePatch,
+    fuzz: usize,
+    dry_run: bool,
+    first_loaded: &mut Option<PathBuf>,
+    report: &mut String,
+) -> AppResult<usize> {
+    let (lines, final_newline) = match ws.editor(path) {
+        Some(ed) => (ed.all_lines().to_vec(), ed.has_final_newline()),
+        None => ws.disk_lines(path)?,
+    };
+
+    let (new_lines, results) = patch::apply_hunks(&lines, &file.hunks, fuzz);
+    let applied = results.iter().filter(|r| matches!(r, HunkResult::Applied { .. })).count();
+    let rejected = results.len() - applied;
+
+    if !dry_run && applied > 0 {
+        if ws.editor(path).is_none() {
+            ws.load(path)?;
+            first_loaded.get_or_insert(path.to_path_buf());
+        }
+        // 新建的文件按惯例以换行结尾
+        let final_newline = final_newline || lines.is_empty();
+        ws.exec_doc_at(path, Box::new(ReplaceContentCommand::new(new_lines, final_newline)))?;
+    }
+
+    let verb = if dry_run { "would patch" } else { "patched" };
+    let _ = writeln!(report, "{} {}: {} applied, {} rejected", verb, path.display(), applied, rejected);
+    for (n, (hunk, result)) in file.hunks.iter().zip(&results).enumerate() {
+        match result {
+            HunkResult::Applied { offset: 0, fuzz: 0 } => {}
+            HunkResult::Applied { offset, fuzz } => {
+                let _ = writeln!(report, "  hunk #{} applied with offset {:+}, fuzz {}", n + 1, offset, fuzz);
+            }
+            HunkResult::Rejected => {
+                let _ = writeln!(report, "  hunk #{} rejected (expected at line {})", n + 1, hunk.old_start);
+            }
+        }
+    }
+    Ok(rejected)
+}
+
+pub const PATCH_COMMAND: CommandDef = CommandDef {
+    name: "patch",
+    handler: cmd_patch,
+};
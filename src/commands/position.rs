@@ -0,0 +1,123 @@
+//! commands/position.rs
+//!
+//! 当前位置与书签（随工作区 / 会话一起保存）：
+//! - goto <line[:col]>                  设置当前位置并显示该行
+//! - where                              显示当前位置、上次 show 的范围与书签
+//! - bookmark <name> [line[:col]]       在指定位置（默认当前位置）设书签
+//! - bookmark --delete <name>
+//! - jump <name>                        跳到书签位置
+
+use crate::{
+    application::Application,
+    outcome::Outcome,
+    persist::Position,
+    error::{AppError, AppResult},
+    commands::util,
+};
+use super::CommandDef;
+
+/// 解析 "line" 或 "line:col"，列号缺省为 1
+fn parse_position(s: &str) -> AppResult<Position> {
+    let (line, col) = if s.contains(':') {
+        util::parse_pos(s)?
+    } else {
+        let line = s
+            .parse()
+            .map_err(|_| AppError::InvalidArgs(format!("invalid line number: {}", s)))?;
+        (line, 1)
+    };
+    Ok(Position { line, col })
+}
+
+/// 设置位置并返回该行内容，goto 与 jump 共用
+fn move_to(app: &mut Application, pos: Position) -> AppResult<String> {
+    app.workspace.set_position(pos)?;
+    let ed = app.workspace.active_editor()?;
+    let text = ed.all_lines().get(pos.line - 1).map(|s| s.as_str()).unwrap_or("");
+    Ok(format!("{}: {}", pos.line, text))
+}
+
+pub fn cmd_goto(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let spec = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("goto <line[:col]>".into()))?;
+    let line = move_to(app, parse_position(spec)?)?;
+    Ok(Outcome {
+        print: Some(line),
+        log: Some(format!("goto {}", spec)),
+        exit: false,
+    })
+}
+
+pub const GOTO_COMMAND: CommandDef = CommandDef {
+    name: "goto",
+    handler: cmd_goto,
+};
+
+pub fn cmd_where(app: &mut Application, _args: &[String]) -> AppResult<Outcome> {
+    let ed = app.workspace.active_editor()?;
+    let mut out = match ed.position() {
+        Some(p) => format!("position: {}", p),
+        None => "position: (none)".to_string(),
+    };
+    if let Some((s, e)) = ed.view() {
+        out.push_str(&format!("\nlast show: {}:{}", s, e));
+    }
+    for (name, pos) in ed.bookmarks() {
+        out.push_str(&format!("\nbookmark {}: {}", name, pos));
+    }
+    Ok(Outcome::print(out))
+}
+
+pub const WHERE_COMMAND: CommandDef = CommandDef {
+    name: "where",
+    handler: cmd_where,
+};
+
+pub fn cmd_bookmark(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    const USAGE: &str = "bookmark <name> [line[:col]] | bookmark --delete <name>";
+    match args {
+        [flag, name] if flag == "--delete" => app.workspace.remove_bookmark(name)?,
+        [name] | [name, _] if !name.starts_with('-') => {
+            let pos = match args.get(1) {
+                Some(spec) => parse_position(spec)?,
+                None => app
+                    .workspace
+                    .active_editor()?
+                    .position()
+                    .unwrap_or(Position { line: 1, col: 1 }),
+            };
+            app.workspace.set_bookmark(name, pos)?;
+        }
+        _ => return Err(AppError::InvalidArgs(USAGE.into())),
+    }
+    Ok(Outcome::log(format!("bookmark {}", args.join(" "))))
+}
+
+pub const BOOKMARK_COMMAND: CommandDef = CommandDef {
+    name: "bookmark",
+    handler: cmd_bookmark,
+};
+
+pub fn cmd_jump(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let name = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("jump <bookmark>".into()))?;
+    let pos = *app
+        .workspace
+        .active_editor()?
+        .bookmarks()
+        .get(name)
+        .ok_or_else(|| AppError::InvalidArgs(format!("no such bookmark: {}", name)))?;
+    let line = move_to(app, pos)?;
+    Ok(Outcome {
+        print: Some(line),
+        log: Some(format!("jump {}", name)),
+        exit: false,
+    })
+}
+
+pub const JUMP_COMMAND: CommandDef = CommandDef {
+    name: "jump",
+    handler: cmd_jump,
+};
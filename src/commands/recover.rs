@@ -0,0 +1,76 @@
+//! commands/recover.rs
+//!
+//! 处理启动时发现的交换文件：
+//! - recover            列出待处理的交换文件
+//! - recover <file>|--all   用交换文件内容恢复（文件未打开时自动打开）
+//! - discard <file>|--all   丢弃交换文件
+
+use std::path::PathBuf;
+
+use crate::{
+    application::Application,
+    outcome::Outcome,
+    error::{AppError, AppResult},
+};
+use super::CommandDef;
+
+/// 解析目标：`--all` 表示全部待处理文件，否则按 base_dir 解析单个路径
+fn swap_targets(app: &Application, arg: &str) -> Vec<PathBuf> {
+    if arg == "--all" {
+        app.workspace.pending_swaps()
+    } else {
+        vec![app.workspace.resolve_path(Some(arg))]
+    }
+}
+
+pub fn cmd_recover(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let Some(arg) = args.first() else {
+        let pending = app.workspace.pending_swaps();
+        if pending.is_empty() {
+            return Ok(Outcome::print("no swap files to recover"));
+        }
+        let list: Vec<String> = pending.iter().map(|p| p.display().to_string()).collect();
+        return Ok(Outcome::print(list.join("\n")));
+    };
+
+    let targets = swap_targets(app, arg);
+    if targets.is_empty() {
+        return Err(AppError::InvalidArgs("no swap files to recover".into()));
+    }
+    for path in &targets {
+        app.workspace.recover(path)?;
+    }
+
+    Ok(Outcome {
+        print: Some(format!("recovered {} file(s)", targets.len())),
+        log: Some(format!("recover {}", arg)),
+        exit: false,
+    })
+}
+
+pub const RECOVER_COMMAND: CommandDef = CommandDef {
+    name: "recover",
+    handler: cmd_recover,
+};
+
+pub fn cmd_discard(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let arg = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("discard <file>|--all".into()))?;
+
+    let targets = swap_targets(app, arg);
+    for path in &targets {
+        app.workspace.discard_swap(path)?;
+    }
+
+    Ok(Outcome {
+        print: Some(format!("discarded {} swap file(s)", targets.len())),
+        log: Some(format!("discard {}", arg)),
+        exit: false,
+    })
+}
+
+pub const DISCARD_COMMAND: CommandDef = CommandDef {
+    name: "discard",
+    handler: cmd_discard,
+};
@@ -0,0 +1,122 @@
+//! commands/register.rs
+//!
+//! 寄存器（内部剪贴板），在所有打开的文件之间共享：
+//! - yank <range> [register]：把整行复制进寄存器
+//! - put <line:col> [register]：取出寄存器内容。整行内容插在第 line 行之前（col 忽略），
+//!   字符内容插在 (line, col) 处
+//! - registers：列出所有非空寄存器
+//!
+//! 省略寄存器名时使用无名寄存器 `"`；`-` 保存最近一次删除的内容。
+
+use crate::{
+    application::Application,
+    outcome::Outcome,
+    error::{AppError, AppResult},
+    editor::Editor,
+    persist::Register,
+    workspace::UNNAMED_REGISTER,
+    commands::{doc_command::DocCommand, util},
+};
+use super::CommandDef;
+
+// ==== DocCommand ====
+
+struct PutRegisterCommand {
+    line: usize,
+    col: usize,
+    reg: Register,
+}
+
+impl PutRegisterCommand {
+    fn new(line: usize, col: usize, reg: Register) -> Self {
+        Self { line, col, reg }
+    }
+}
+
+impl DocCommand for PutRegisterCommand {
+    fn execute(&mut self, ed: &mut Editor) -> AppResult<()> {
+        match &self.reg {
+            Register::Chars(text) => ed.insert_text(self.line, self.col, text),
+            Register::Lines(lines) => {
+                ed.splice_lines(self.line, 0, lines.clone())?;
+                Ok(())
+            }
+        }
+    }
+
+    fn undo(&mut self, ed: &mut Editor) -> AppResult<()> {
+        match &self.reg {
+            Register::Chars(text) => ed.delete_text(self.line, self.col, text.len()),
+            Register::Lines(lines) => {
+                ed.splice_lines(self.line, lines.len(), Vec::new())?;
+                Ok(())
+            }
+        }
+    }
+}
+
+// ==== CLI ====
+
+fn parse_register(arg: Option<&String>) -> AppResult<char> {
+    let Some(s) = arg else { return Ok(UNNAMED_REGISTER) };
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(AppError::InvalidArgs(format!("invalid register name: {}", s))),
+    }
+}
+
+pub fn cmd_yank(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let spec = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("yank <range> [register]".into()))?;
+    let name = parse_register(args.get(1))?;
+
+    let (s, e) = util::active_range(app, spec)?;
+    let lines = app.workspace.active_editor()?.lines_range(s, e)?;
+    let count = lines.len();
+    app.workspace.set_register(name, Register::Lines(lines))?;
+
+    Ok(Outcome {
+        print: Some(format!("{} line(s) yanked into \"{}", count, name)),
+        log: Some(format!("yank {} {}", spec, name)),
+        exit: false,
+    })
+}
+
+pub const YANK_COMMAND: CommandDef = CommandDef {
+    name: "yank",
+    handler: cmd_yank,
+};
+
+pub fn cmd_put(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let pos = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("put <line:col> [register] (col is ignored for whole lines)".into()))?;
+    let name = parse_register(args.get(1))?;
+
+    let (line, col) = util::parse_pos(pos)?;
+    let reg = app.workspace.get_register(name)?.clone();
+    let cmd = PutRegisterCommand::new(line, col, reg);
+    app.workspace.exec_doc(Box::new(cmd))?;
+
+    Ok(Outcome::log(format!("put {} {}", pos, name)))
+}
+
+pub const PUT_COMMAND: CommandDef = CommandDef {
+    name: "put",
+    handler: cmd_put,
+};
+
+pub fn cmd_registers(app: &mut Application, _args: &[String]) -> AppResult<Outcome> {
+    let list = app.workspace.list_registers();
+    if list.is_empty() {
+        return Ok(Outcome::print("<no registers>"));
+    }
+    Ok(Outcome::print(list))
+}
+
+pub const REGISTERS_COMMAND: CommandDef = CommandDef {
+    name: "registers",
+    handler: cmd_registers,
+};
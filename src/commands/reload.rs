@@ -0,0 +1,65 @@
+//! commands/reload.rs
+//!
+//! 处理磁盘上被其他程序改过的文件：
+//! - reload [file] [--force]   用磁盘内容替换缓冲区（可 undo）；有未保存修改时需要 --force
+//! - diff-disk [file]          显示磁盘内容到缓冲区的差异
+
+use crate::{
+    application::Application,
+    diff,
+    outcome::Outcome,
+    error::{AppError, AppResult},
+};
+use super::CommandDef;
+
+/// 拆出 `--force` 与可选的文件参数；未给文件时使用活跃文件
+fn target_and_force(app: &Application, args: &[String], usage: &str) -> AppResult<(std::path::PathBuf, bool)> {
+    let force = args.iter().any(|a| a == "--force");
+    let rest: Vec<&String> = args.iter().filter(|a| *a != "--force").collect();
+    let path = match rest.as_slice() {
+        [] => app
+            .workspace
+            .active_file_path()
+            .ok_or_else(|| AppError::InvalidArgs("no active file".into()))?,
+        [file] => app.workspace.resolve_path(Some(file)),
+        _ => return Err(AppError::InvalidArgs(usage.into())),
+    };
+    Ok((path, force))
+}
+
+pub fn cmd_reload(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let (path, force) = target_and_force(app, args, "reload [file] [--force]")?;
+    app.workspace.reload(&path, force)?;
+
+    Ok(Outcome {
+        print: Some(format!("reloaded {}", path.display())),
+        log: Some(format!("reload {}", args.join(" ")).trim_end().to_string()),
+        exit: false,
+    })
+}
+
+pub const RELOAD_COMMAND: CommandDef = CommandDef {
+    name: "reload",
+    handler: cmd_reload,
+};
+
+pub fn cmd_diff_disk(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let (path, force) = target_and_force(app, args, "diff-disk [file]")?;
+    if force {
+        return Err(AppError::InvalidArgs("diff-disk [file]".into()));
+    }
+
+    let ed = app
+        .workspace
+        .editor(&path)
+        .ok_or_else(|| AppError::InvalidArgs(format!("file not open: {}", path.display())))?;
+    let (disk, _) = app.workspace.disk_lines(&path)?;
+    let label = path.display().to_string();
+    let out = diff::unified_diff(&disk, ed.all_lines(), &label, &format!("{} (buffer)", label), 3);
+    Ok(super::diff::render(out))
+}
+
+pub const DIFF_DISK_COMMAND: CommandDef = CommandDef {
+    name: "diff-disk",
+    handler: cmd_diff_disk,
+};
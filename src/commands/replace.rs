@@ -3,6 +3,7 @@ use crate::{
     outcome::Outcome,
     error::{AppError, AppResult},
     editor::Editor,
+    persist::Register,
     commands::{
         doc_command::DocCommand,
         util,
@@ -61,8 +62,11 @@ pub fn cmd_replace(app: &mut Application, args: &[String]) -> AppResult<Outcome>
         .map_err(|_| AppError::InvalidArgs("len must be a number".into()))?;
     let text = args[2].clone();
 
+    // 被替换掉的旧内容同样记入 "- 寄存器
+    let old_text = app.workspace.active_editor()?.peek_text(line, col, len)?;
     let cmd = ReplaceTextCommand::new(line, col, len, text.clone());
     app.workspace.exec_doc(Box::new(cmd))?;
+    app.workspace.record_deleted(Register::Chars(old_text));
 
     Ok(Outcome {
         print: None,
@@ -1,19 +1,25 @@
-use crate::{application::Application, outcome::Outcome, error::AppResult};
+use crate::{application::Application, outcome::Outcome, error::{AppError, AppResult}};
 use super::CommandDef;
 
-pub fn cmd_save(app: &mut Application, _args: &[String]) -> AppResult<Outcome> {
-    match _args.get(0).map(|s| s.as_str()) {
+pub fn cmd_save(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    // --force：即使磁盘上的文件被其他程序改过也覆盖
+    let force = args.iter().any(|a| a == "--force");
+    let rest: Vec<&str> = args.iter().map(|s| s.as_str()).filter(|a| *a != "--force").collect();
+    let suffix = if force { " --force" } else { "" };
+
+    match rest.as_slice() {
         // 没有参数：保存所有已打开文件
-        None => {
-            app.workspace.save_all()?;
-            Ok(Outcome::log("save all"))
+        [] => {
+            app.workspace.save_all(force)?;
+            Ok(Outcome::log(format!("save all{}", suffix)))
         }
         // 有参数：按 base_dir 解析路径，再只保存这个文件
-        Some(raw) => {
+        [raw] => {
             let path = app.workspace.resolve_path(Some(raw));
-            app.workspace.save_file(&path)?;
-            Ok(Outcome::log(&format!("save {}", raw)))
+            app.workspace.save_file(&path, force)?;
+            Ok(Outcome::log(format!("save {}{}", raw, suffix)))
         }
+        _ => Err(AppError::InvalidArgs("save [file] [--force]".into())),
     }
 }
 
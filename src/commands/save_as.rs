@@ -0,0 +1,66 @@
+//! commands/save_as.rs
+//!
+//! - save-as [--force] <path>     把活跃文件写到新路径，之后编辑的就是新文件，原文件不动
+//! - rename [--force] <path>      移动磁盘上的文件，Editor、日志和本地历史一起改到新路径
+//! - duplicate [--force] <path>   把活跃文件另存一份并打开，活跃文件不变
+//!
+//! 目标已存在（磁盘上或已打开）时需要 --force；被覆盖的已打开文件会被关闭。
+
+use crate::{application::Application, outcome::Outcome, error::{AppError, AppResult}};
+use std::path::PathBuf;
+use super::CommandDef;
+
+/// 解析 `[--force] <path>`，返回 (force, 原始参数, 解析后的路径)
+fn target_args(app: &Application, args: &[String], usage: &str) -> AppResult<(bool, String, PathBuf)> {
+    let force = args.iter().any(|a| a == "--force");
+    let rest: Vec<&String> = args.iter().filter(|a| *a != "--force").collect();
+    let [raw] = rest.as_slice() else {
+        return Err(AppError::InvalidArgs(usage.into()));
+    };
+    Ok((force, raw.to_string(), app.workspace.resolve_path(Some(raw))))
+}
+
+pub fn cmd_save_as(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let (force, raw, path) = target_args(app, args, "save-as [--force] <path>")?;
+    app.workspace.save_as(&path, force)?;
+    Ok(Outcome {
+        print: Some(format!("Saved as {}", raw)),
+        log: Some(format!("save-as {}", args.join(" "))),
+        exit: false,
+    })
+}
+
+pub const SAVE_AS_COMMAND: CommandDef = CommandDef {
+    name: "save-as",
+    handler: cmd_save_as,
+};
+
+pub fn cmd_rename(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let (force, raw, path) = target_args(app, args, "rename [--force] <path>")?;
+    app.workspace.rename(&path, force)?;
+    Ok(Outcome {
+        print: Some(format!("Renamed to {}", raw)),
+        log: Some(format!("rename {}", args.join(" "))),
+        exit: false,
+    })
+}
+
+pub const RENAME_COMMAND: CommandDef = CommandDef {
+    name: "rename",
+    handler: cmd_rename,
+};
+
+pub fn cmd_duplicate(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let (force, raw, path) = target_args(app, args, "duplicate [--force] <path>")?;
+    app.workspace.duplicate(&path, force)?;
+    Ok(Outcome {
+        print: Some(format!("Duplicated to {}", raw)),
+        log: Some(format!("duplicate {}", args.join(" "))),
+        exit: false,
+    })
+}
+
+pub const DUPLICATE_COMMAND: CommandDef = CommandDef {
+    name: "duplicate",
+    handler: cmd_duplicate,
+};
@@ -0,0 +1,68 @@
+//! commands/save_hook.rs
+//!
+//! save-hook                               显示工作区默认与当前文件的钩子设置
+//! save-hook <name> on|off [--local]       修改工作区默认；--local 只改当前文件
+//! save-hook reset                         当前文件恢复为工作区默认
+//!
+//! name: strip-trailing | collapse-blank | final-newline
+
+use crate::{
+    application::Application,
+    outcome::Outcome,
+    error::{AppError, AppResult},
+};
+use super::CommandDef;
+
+const USAGE: &str = "save-hook [<name> on|off [--local] | reset]";
+
+pub fn cmd_save_hook(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    match args.first().map(|s| s.as_str()) {
+        None => {
+            let mut out = format!("[workspace]\n{}", app.workspace.save_hooks().describe());
+            if let Ok(ed) = app.workspace.active_editor()
+                && let Some(local) = ed.save_hooks()
+            {
+                out.push_str(&format!("\n[local]\n{}", local.describe()));
+            }
+            Ok(Outcome::print(out))
+        }
+        Some("reset") => {
+            app.workspace.set_active_save_hooks(None)?;
+            Ok(Outcome::log("save-hook reset"))
+        }
+        Some(name) => {
+            let on = match args.get(1).map(|s| s.as_str()) {
+                Some("on") => true,
+                Some("off") => false,
+                _ => return Err(AppError::InvalidArgs(USAGE.into())),
+            };
+            let local = match args.get(2).map(|s| s.as_str()) {
+                None => false,
+                Some("--local") => true,
+                Some(_) => return Err(AppError::InvalidArgs(USAGE.into())),
+            };
+
+            if local {
+                // 首次设置时以工作区默认为起点
+                let mut hooks = app
+                    .workspace
+                    .active_editor()?
+                    .save_hooks()
+                    .unwrap_or(app.workspace.save_hooks());
+                hooks.set(name, on)?;
+                app.workspace.set_active_save_hooks(Some(hooks))?;
+            } else {
+                let mut hooks = app.workspace.save_hooks();
+                hooks.set(name, on)?;
+                app.workspace.set_save_hooks(hooks);
+            }
+
+            Ok(Outcome::log(format!("save-hook {}", args.join(" "))))
+        }
+    }
+}
+
+pub const SAVE_HOOK_COMMAND: CommandDef = CommandDef {
+    name: "save-hook",
+    handler: cmd_save_hook,
+};
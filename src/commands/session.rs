@@ -0,0 +1,96 @@
+//! commands/session.rs
+//!
+//! 命名会话：把当前打开的文件、活跃文件与各文件状态整体存下来，方便在不同任务之间切换。
+//! - session-save <name>
+//! - session-load <name> [--force]     有未保存的修改时需要 --force
+//! - session-list
+//! - session-delete <name>
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    application::Application,
+    outcome::Outcome,
+    error::{AppError, AppResult},
+};
+use super::CommandDef;
+
+fn session_name<'a>(args: &'a [String], usage: &str) -> AppResult<&'a str> {
+    args.first()
+        .map(|s| s.as_str())
+        .ok_or_else(|| AppError::InvalidArgs(usage.into()))
+}
+
+pub fn cmd_session_save(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let name = session_name(args, "session-save <name>")?;
+    app.workspace.save_session(name)?;
+    Ok(Outcome {
+        print: Some(format!("session {} saved", name)),
+        log: Some(format!("session-save {}", name)),
+        exit: false,
+    })
+}
+
+pub const SESSION_SAVE_COMMAND: CommandDef = CommandDef {
+    name: "session-save",
+    handler: cmd_session_save,
+};
+
+pub fn cmd_session_load(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let name = session_name(args, "session-load <name> [--force]")?;
+    let force = match args.get(1).map(|s| s.as_str()) {
+        None => false,
+        Some("--force") => true,
+        Some(_) => return Err(AppError::InvalidArgs("session-load <name> [--force]".into())),
+    };
+    let warnings = app.workspace.load_session(name, force)?;
+
+    let mut out = format!("session {} loaded ({} file(s))", name, app.workspace.editor_count());
+    for w in warnings {
+        out.push_str(&format!("\n[warn] {}", w));
+    }
+    Ok(Outcome {
+        print: Some(out),
+        log: Some(format!("session-load {}", args.join(" "))),
+        exit: false,
+    })
+}
+
+pub const SESSION_LOAD_COMMAND: CommandDef = CommandDef {
+    name: "session-load",
+    handler: cmd_session_load,
+};
+
+pub fn cmd_session_list(app: &mut Application, _args: &[String]) -> AppResult<Outcome> {
+    let sessions = app.workspace.list_sessions()?;
+    if sessions.is_empty() {
+        return Ok(Outcome::print("no saved sessions"));
+    }
+    let list: Vec<String> = sessions
+        .iter()
+        .map(|(name, mtime)| {
+            format!("{}  (saved {})", name, DateTime::<Local>::from(*mtime).format("%Y-%m-%d %H:%M:%S"))
+        })
+        .collect();
+    Ok(Outcome::print(list.join("\n")))
+}
+
+pub const SESSION_LIST_COMMAND: CommandDef = CommandDef {
+    name: "session-list",
+    handler: cmd_session_list,
+};
+
+pub fn cmd_session_delete(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let name = session_name(args, "session-delete <name>")?;
+    app.workspace.delete_session(name)?;
+    Ok(Outcome {
+        print: Some(format!("session {} deleted", name)),
+        log: Some(format!("session-delete {}", name)),
+        exit: false,
+    })
+}
+
+pub const SESSION_DELETE_COMMAND: CommandDef = CommandDef {
+    name: "session-delete",
+    handler: cmd_session_delete,
+};
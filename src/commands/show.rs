@@ -1,18 +1,34 @@
 use crate::{
     application::Application, 
     outcome::Outcome, 
-    error::{AppResult, AppError}
+    error::{AppError, AppResult},
+    commands::util,
 };
 use super::CommandDef;
 
+/// show [range|--last]：--last 重新显示上一次查看的范围（会随工作区一起保存）
 pub fn cmd_show(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
     let (start, end) 
-        = match args.get(0).map(|s| s.as_str()) {
+        = match args.first().map(|s| s.as_str()) {
         None => (None, None),
-        Some(spec) => parse_range_spec(spec)?,
+        Some("--last") => {
+            let (s, e) = app
+                .workspace
+                .active_editor()?
+                .view()
+                .ok_or_else(|| AppError::InvalidArgs("no previous show range".into()))?;
+            (Some(s), Some(e))
+        }
+        Some(spec) => util::parse_range(spec)?,
     };
 
     let content = app.workspace.show(start, end)?;
+    // 记录实际显示的范围（与 show 一样截断到文件内）；空文件没有范围可记
+    let n = app.workspace.active_editor()?.count_lines();
+    if n > 0 {
+        let range = (start.unwrap_or(1).clamp(1, n), end.unwrap_or(n).clamp(1, n));
+        app.workspace.set_view(Some(range))?;
+    }
     Ok(Outcome::print(content))
 }
 
@@ -20,28 +36,3 @@ pub const SHOW_COMMAND: CommandDef = CommandDef {
     name: "show",
     handler: cmd_show,
 };
-
-fn parse_range_spec(spec: &str) -> AppResult<(Option<usize>, Option<usize>)> {
-    // 仅数字、冒号、空白
-    let s = spec.trim();
-
-    // 只有起点： "N"
-    if !s.contains(':') {
-        let n = parse_pos_usize(s)?;
-        return Ok((Some(n), None));
-    }
-
-    // 形如 "A:B"；支持空 A / 空 B
-    let (a, b) = s.split_once(':').unwrap(); // 一定有冒号
-    let start = if a.trim().is_empty() { None } else { Some(parse_pos_usize(a.trim())?) };
-    let end   = if b.trim().is_empty() { None } else { Some(parse_pos_usize(b.trim())?) };
-    Ok((start, end))
-}
-
-fn parse_pos_usize(s: &str) -> AppResult<usize> {
-    let v: usize = s.parse().map_err(|_| AppError::InvalidArgs(format!("invalid number: {s}")))?;
-    if v == 0 {
-        return Err(AppError::InvalidArgs("line numbers are 1-based (>=1)".into()));
-    }
-    Ok(v)
-}
\ No newline at end of file
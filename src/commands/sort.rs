@@ -0,0 +1,311 @@
+//! commands/sort.rs
+//!
+//! 对行范围做整体重排，每条命令都是一次 ReplaceLinesCommand，可整体撤销：
+//! - sort <range> [-n] [-r] [-i] [-s] [-k N] [-t SEP]
+//!   -n 按数值，-r 逆序，-i 忽略大小写，-s 稳定排序（键相同时保持原顺序），
+//!   -k 按第 N 列排序（默认以空白分列，-t 指定分隔符）
+//! - uniq <range> [-a] [-i]：去掉相邻重复行；-a 去掉所有重复行，只保留第一次出现
+//! - reverse <range>：倒转行顺序
+//! - shuffle <range> [seed]：按种子确定性打乱，省略种子时随机生成并记入日志
+
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    application::Application,
+    outcome::Outcome,
+    error::{AppError, AppResult},
+    commands::util,
+};
+use super::CommandDef;
+
+#[derive(Default)]
+struct SortOptions {
+    numeric: bool,
+    reverse: bool,
+    ignore_case: bool,
+    stable: bool,
+    key: Option<usize>,
+    sep: Option<String>,
+}
+
+impl SortOptions {
+    fn parse(args: &[String]) -> AppResult<Self> {
+        let mut opts = SortOptions::default();
+        let mut it = args.iter();
+        while let Some(arg) = it.next() {
+            match arg.as_str() {
+                "-k" => {
+                    let v = it
+                        .next()
+                        .ok_or_else(|| AppError::InvalidArgs("-k needs a column number".into()))?;
+                    let n = v
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&n| n >= 1)
+                        .ok_or_else(|| AppError::InvalidArgs(format!("invalid key column: {}", v)))?;
+                    opts.key = Some(n);
+                }
+                "-t" => {
+                    let v = it
+                        .next()
+                        .filter(|v| !v.is_empty())
+                        .ok_or_else(|| AppError::InvalidArgs("-t needs a separator".into()))?;
+                    opts.sep = Some(v.clone());
+                }
+                // 布尔选项允许合写，如 -nr
+                flags if flags.len() > 1 && flags.starts_with('-') => {
+                    for c in flags[1..].chars() {
+                        match c {
+                            'n' => opts.numeric = true,
+                            'r' => opts.reverse = true,
+                            'i' => opts.ignore_case = true,
+                            's' => opts.stable = true,
+                            _ => return Err(AppError::InvalidArgs(format!("unknown sort option: -{}", c))),
+                        }
+                    }
+                }
+                other => return Err(AppError::InvalidArgs(format!("unexpected argument: {}", other))),
+            }
+        }
+        Ok(opts)
+    }
+
+    /// 取出参与比较的键
+    fn key_of<'a>(&self, line: &'a str) -> &'a str {
+        let Some(k) = self.key else { return line };
+        let field = match &self.sep {
+            Some(sep) => line.split(sep.as_str()).nth(k - 1),
+            None => line.split_whitespace().nth(k - 1),
+        };
+        field.unwrap_or("")
+    }
+
+    fn compare_keys(&self, a: &str, b: &str) -> Ordering {
+        if self.numeric {
+            // 解析不出数字的键（包括 nan）排在所有数字之前；
+            // NaN 参与比较会破坏全序，sort_by 可能因此 panic
+            let num = |s: &str| s.trim().parse::<f64>().ok().filter(|n| !n.is_nan());
+            return match (num(a), num(b)) {
+                (Some(x), Some(y)) => x.total_cmp(&y),
+                (x, y) => x.is_some().cmp(&y.is_some()),
+            };
+        }
+        if self.ignore_case {
+            return a.to_lowercase().cmp(&b.to_lowercase());
+        }
+        a.cmp(b)
+    }
+}
+
+fn sort_lines(lines: &mut [String], opts: &SortOptions) {
+    lines.sort_by(|a, b| {
+        let mut ord = opts.compare_keys(opts.key_of(a), opts.key_of(b));
+        // 非稳定模式下，键相同时再按整行比较，结果与原顺序无关
+        if ord == Ordering::Equal && !opts.stable {
+            ord = a.cmp(b);
+        }
+        if opts.reverse { ord.reverse() } else { ord }
+    });
+}
+
+fn dedupe_lines(lines: Vec<String>, all: bool, ignore_case: bool) -> Vec<String> {
+    let norm = |s: &str| if ignore_case { s.to_lowercase() } else { s.to_string() };
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut seen = HashSet::new();
+    for line in lines {
+        let key = norm(&line);
+        let dup = if all {
+            !seen.insert(key)
+        } else {
+            out.last().is_some_and(|prev| norm(prev) == key)
+        };
+        if !dup {
+            out.push(line);
+        }
+    }
+    out
+}
+
+/// splitmix64：简单、可复现的伪随机数，避免为洗牌引入额外依赖
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+fn shuffle_lines(lines: &mut [String], seed: u64) {
+    // Fisher–Yates
+    let mut rng = SplitMix64(seed);
+    for i in (1..lines.len()).rev() {
+        let j = (rng.next() % (i as u64 + 1)) as usize;
+        lines.swap(i, j);
+    }
+}
+
+// ==== CLI ====
+
+pub fn cmd_sort(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let spec = args.first().ok_or_else(|| {
+        AppError::InvalidArgs("sort <range> [-n] [-r] [-i] [-s] [-k N] [-t SEP]".into())
+    })?;
+    let opts = SortOptions::parse(&args[1..])?;
+
+    util::rewrite_range(app, spec, |mut lines| {
+        sort_lines(&mut lines, &opts);
+        lines
+    })?;
+
+    Ok(Outcome::log(format!("sort {}", shell_words::join(args))))
+}
+
+pub const SORT_COMMAND: CommandDef = CommandDef {
+    name: "sort",
+    handler: cmd_sort,
+};
+
+pub fn cmd_uniq(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let spec = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("uniq <range> [-a] [-i]".into()))?;
+    let mut all = false;
+    let mut ignore_case = false;
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "-a" => all = true,
+            "-i" => ignore_case = true,
+            other => return Err(AppError::InvalidArgs(format!("unknown uniq option: {}", other))),
+        }
+    }
+
+    util::rewrite_range(app, spec, |lines| dedupe_lines(lines, all, ignore_case))?;
+
+    Ok(Outcome::log(format!("uniq {}", shell_words::join(args))))
+}
+
+pub const UNIQ_COMMAND: CommandDef = CommandDef {
+    name: "uniq",
+    handler: cmd_uniq,
+};
+
+pub fn cmd_reverse(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let spec = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("reverse <range>".into()))?;
+
+    util::rewrite_range(app, spec, |mut lines| {
+        lines.reverse();
+        lines
+    })?;
+
+    Ok(Outcome::log(format!("reverse {}", spec)))
+}
+
+pub const REVERSE_COMMAND: CommandDef = CommandDef {
+    name: "reverse",
+    handler: cmd_reverse,
+};
+
+pub fn cmd_shuffle(app: &mut Application, args: &[String]) -> AppResult<Outcome> {
+    let spec = args
+        .first()
+        .ok_or_else(|| AppError::InvalidArgs("shuffle <range> [seed]".into()))?;
+    let seed = match args.get(1) {
+        Some(s) => s
+            .parse::<u64>()
+            .map_err(|_| AppError::InvalidArgs(format!("invalid seed: {}", s)))?,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0),
+    };
+
+    util::rewrite_range(app, spec, |mut lines| {
+        shuffle_lines(&mut lines, seed);
+        lines
+    })?;
+
+    // 日志里总是带上种子，方便复现
+    Ok(Outcome::log(format!("shuffle {} {}", spec, seed)))
+}
+
+pub const SHUFFLE_COMMAND: CommandDef = CommandDef {
+    name: "shuffle",
+    handler: cmd_shuffle,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::strings;
+
+    fn opts(args: &[&str]) -> SortOptions {
+        SortOptions::parse(&strings(args)).expect("parse options failed")
+    }
+
+    #[test]
+    fn sort_numeric_reverse_and_case_insensitive() {
+        let mut lines = strings(&["10", "9", "x", "100"]);
+        sort_lines(&mut lines, &opts(&["-n"]));
+        assert_eq!(lines, strings(&["x", "9", "10", "100"]));
+
+        sort_lines(&mut lines, &opts(&["-nr"]));
+        assert_eq!(lines, strings(&["100", "10", "9", "x"]));
+
+        let mut lines = strings(&["2", "nan", "1", "x", "NaN", "-inf"]);
+        sort_lines(&mut lines, &opts(&["-n"]));
+        assert_eq!(lines, strings(&["NaN", "nan", "x", "-inf", "1", "2"]));
+
+        let mut lines = strings(&["b", "B", "a"]);
+        sort_lines(&mut lines, &opts(&["-i"]));
+        assert_eq!(lines, strings(&["a", "B", "b"]));
+    }
+
+    #[test]
+    fn sort_by_key_column_stable_and_not() {
+        let input = strings(&["z,2", "a,1", "y,2", "b,1"]);
+
+        let mut lines = input.clone();
+        sort_lines(&mut lines, &opts(&["-k", "2", "-t", ",", "-s"]));
+        assert_eq!(lines, strings(&["a,1", "b,1", "z,2", "y,2"]));
+
+        // 不稳定模式下同键行再按整行排序
+        let mut lines = input;
+        sort_lines(&mut lines, &opts(&["-k", "2", "-t", ","]));
+        assert_eq!(lines, strings(&["a,1", "b,1", "y,2", "z,2"]));
+
+        assert!(SortOptions::parse(&strings(&["-k", "0"])).is_err());
+        assert!(SortOptions::parse(&strings(&["-q"])).is_err());
+    }
+
+    #[test]
+    fn uniq_adjacent_and_all() {
+        let lines = strings(&["a", "a", "b", "A", "a"]);
+        assert_eq!(dedupe_lines(lines.clone(), false, false), strings(&["a", "b", "A", "a"]));
+        assert_eq!(dedupe_lines(lines.clone(), true, false), strings(&["a", "b", "A"]));
+        assert_eq!(dedupe_lines(lines, true, true), strings(&["a", "b"]));
+    }
+
+    #[test]
+    fn shuffle_is_deterministic_for_a_seed() {
+        let input = strings(&["1", "2", "3", "4", "5", "6"]);
+        let mut a = input.clone();
+        let mut b = input.clone();
+        shuffle_lines(&mut a, 42);
+        shuffle_lines(&mut b, 42);
+        assert_eq!(a, b);
+
+        let mut sorted = a.clone();
+        sorted.sort();
+        assert_eq!(sorted, input);
+    }
+}
@@ -1,4 +1,8 @@
-use crate::error::{AppError, AppResult};
+use crate::{
+    application::Application,
+    error::{AppError, AppResult},
+    commands::doc_command::ReplaceLinesCommand,
+};
 
 pub fn parse_pos(s: &str) -> AppResult<(usize, usize)> {
     let mut parts = s.split(':');
@@ -20,3 +24,54 @@ pub fn parse_pos(s: &str) -> AppResult<(usize, usize)> {
 
     Ok((line, col))
 }
+
+/// 解析 `show` 风格的行范围："N"、"A:B"、":B"、"A:"，端点缺省时返回 None。
+pub fn parse_range(spec: &str) -> AppResult<(Option<usize>, Option<usize>)> {
+    // 仅数字、冒号、空白
+    let s = spec.trim();
+
+    // 只有起点： "N"
+    if !s.contains(':') {
+        let n = parse_line_no(s)?;
+        return Ok((Some(n), None));
+    }
+
+    // 形如 "A:B"；支持空 A / 空 B
+    let (a, b) = s.split_once(':').unwrap(); // 一定有冒号
+    let start = if a.trim().is_empty() { None } else { Some(parse_line_no(a.trim())?) };
+    let end   = if b.trim().is_empty() { None } else { Some(parse_line_no(b.trim())?) };
+    Ok((start, end))
+}
+
+fn parse_line_no(s: &str) -> AppResult<usize> {
+    let v: usize = s.parse().map_err(|_| AppError::InvalidArgs(format!("invalid number: {s}")))?;
+    if v == 0 {
+        return Err(AppError::InvalidArgs("line numbers are 1-based (>=1)".into()));
+    }
+    Ok(v)
+}
+
+/// 解析范围参数，并按当前活跃文件落到具体的 [start, end] 行号。
+pub fn active_range(app: &Application, spec: &str) -> AppResult<(usize, usize)> {
+    let (start, end) = parse_range(spec)?;
+    app.workspace.active_editor()?.resolve_range(start, end)
+}
+
+/// 解析目标行号：表示“放到第 N 行之后”，0 表示文件开头。
+pub fn parse_dest(s: &str) -> AppResult<usize> {
+    s.trim()
+        .parse::<usize>()
+        .map_err(|_| AppError::InvalidArgs(format!("invalid destination line: {s}")))
+}
+
+/// 读出范围内的行，变换后作为一次 ReplaceLinesCommand 写回（可整体撤销）
+pub fn rewrite_range(
+    app: &mut Application,
+    spec: &str,
+    f: impl FnOnce(Vec<String>) -> Vec<String>,
+) -> AppResult<()> {
+    let (s, e) = active_range(app, spec)?;
+    let lines = app.workspace.active_editor()?.lines_range(s, e)?;
+    let cmd = ReplaceLinesCommand::new(s, e - s + 1, f(lines));
+    app.workspace.exec_doc(Box::new(cmd))
+}
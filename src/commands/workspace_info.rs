@@ -0,0 +1,28 @@
+//! commands/workspace-info
+//!
+//! 显示启动时从哪个快照、哪个格式版本恢复了什么，以及恢复时的警告。
+
+use crate::{application::Application, outcome::Outcome, error::AppResult};
+use super::CommandDef;
+
+pub fn cmd_workspace_info(app: &mut Application, _args: &[String]) -> AppResult<Outcome> {
+    let mut out = app.restore.describe();
+    out.push_str(&format!(
+        "\nbase dir: {}\nopen files now: {}",
+        app.workspace.get_base_dir().display(),
+        app.workspace.editor_count()
+    ));
+    if let Some(sb) = app.workspace.sandbox() {
+        let roots: Vec<String> = sb.roots().iter().map(|r| r.display().to_string()).collect();
+        out.push_str(&format!("\nsandbox: on ({})", roots.join(", ")));
+    }
+    if app.workspace.view_only() {
+        out.push_str("\nmode: view (read-only)");
+    }
+    Ok(Outcome::print(out))
+}
+
+pub const WORKSPACE_INFO_COMMAND: CommandDef = CommandDef {
+    name: "workspace-info",
+    handler: cmd_workspace_info,
+};
@@ -0,0 +1,257 @@
+//! 按行比较两段文本，生成编辑序列与 unified diff 文本。
+
+use std::fmt::Write;
+
+/// 编辑序列中的一步，下标均从 0 起
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffOp {
+    /// a[i] 与 b[j] 相同
+    Equal(usize, usize),
+    /// 删除 a[i]
+    Delete(usize),
+    /// 插入 b[j]
+    Insert(usize),
+}
+
+// 中间不同部分超过这个规模时不再求 LCS，直接视为整段替换，避免内存爆炸
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// 计算把 a 变成 b 的编辑序列。
+/// 先去掉公共前后缀，中间部分用 LCS 动态规划求最短编辑。
+pub fn diff_lines(a: &[String], b: &[String]) -> Vec<DiffOp> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    let mut ops: Vec<DiffOp> = (0..prefix).map(|i| DiffOp::Equal(i, i)).collect();
+
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+    let (n, m) = (a_mid.len(), b_mid.len());
+
+    if n * m > MAX_LCS_CELLS {
+        ops.extend((0..n).map(|i| DiffOp::Delete(prefix + i)));
+        ops.extend((0..m).map(|j| DiffOp::Insert(prefix + j)));
+    } else {
+        // lcs[i][j]：a_mid[i..] 与 b_mid[j..] 的最长公共子序列长度
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if a_mid[i] == b_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && a_mid[i] == b_mid[j] {
+                ops.push(DiffOp::Equal(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j < m && (i == n || lcs[i][j + 1] > lcs[i + 1][j]) {
+                // 只有插入严格更优时才先插；平局时先删后插，与 diff -u 的顺序一致
+                ops.push(DiffOp::Insert(prefix + j));
+                j += 1;
+            } else {
+                ops.push(DiffOp::Delete(prefix + i));
+                i += 1;
+            }
+        }
+    }
+
+    let (a_tail, b_tail) = (a.len() - suffix, b.len() - suffix);
+    ops.extend((0..suffix).map(|k| DiffOp::Equal(a_tail + k, b_tail + k)));
+    ops
+}
+
+/// 生成 unified diff；两边相同时返回空字符串
+pub fn unified_diff(a: &[String], b: &[String], a_label: &str, b_label: &str, context: usize) -> String {
+    let ops = diff_lines(a, b);
+    if ops.iter().all(|op| matches!(op, DiffOp::Equal(..))) {
+        return String::new();
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "--- {}", a_label);
+    let _ = writeln!(out, "+++ {}", b_label);
+
+    for (start, end) in hunk_ranges(&ops, context) {
+        let hunk = &ops[start..end];
+        // hunk 起点在 a / b 中的位置：取第一步涉及的下标
+        let a_start = hunk
+            .iter()
+            .find_map(|op| match op {
+                DiffOp::Equal(i, _) | DiffOp::Delete(i) => Some(*i),
+                DiffOp::Insert(_) => None,
+            })
+            .unwrap_or_else(|| a_pos_before(&ops, start));
+        let b_start = hunk
+            .iter()
+            .find_map(|op| match op {
+                DiffOp::Equal(_, j) | DiffOp::Insert(j) => Some(*j),
+                DiffOp::Delete(_) => None,
+            })
+            .unwrap_or_else(|| b_pos_before(&ops, start));
+        let a_len = hunk.iter().filter(|op| !matches!(op, DiffOp::Insert(_))).count();
+        let b_len = hunk.iter().filter(|op| !matches!(op, DiffOp::Delete(_))).count();
+
+        let _ = writeln!(
+            out,
+            "@@ -{} +{} @@",
+            hunk_header_range(a_start, a_len),
+            hunk_header_range(b_start, b_len)
+        );
+        for op in hunk {
+            let _ = match op {
+                DiffOp::Equal(i, _) => writeln!(out, " {}", a[*i]),
+                DiffOp::Delete(i) => writeln!(out, "-{}", a[*i]),
+                DiffOp::Insert(j) => writeln!(out, "+{}", b[*j]),
+            };
+        }
+    }
+    out
+}
+
+/// 给 unified diff 文本加上 ANSI 颜色：文件头加粗，hunk 头青色，删除红色，插入绿色。
+/// 文件头只出现在第一个 hunk 之前；之后以 `--- ` 开头的是内容为 `-- …` 的删除行
+pub fn colorize(diff: &str) -> String {
+    const RESET: &str = "\x1b[0m";
+    let mut out = String::with_capacity(diff.len() * 2);
+    let mut in_header = true;
+    for line in diff.lines() {
+        let color = if in_header && (line.starts_with("--- ") || line.starts_with("+++ ")) {
+            Some("\x1b[1m")
+        } else if line.starts_with("@@") {
+            in_header = false;
+            Some("\x1b[36m")
+        } else if line.starts_with('-') {
+            Some("\x1b[31m")
+        } else if line.starts_with('+') {
+            Some("\x1b[32m")
+        } else {
+            None
+        };
+        match color {
+            Some(c) => {
+                let _ = writeln!(out, "{}{}{}", c, line, RESET);
+            }
+            None => {
+                let _ = writeln!(out, "{}", line);
+            }
+        }
+    }
+    out
+}
+
+/// 按上下文行数把编辑序列切成若干 hunk，返回每个 hunk 在 ops 中的 [start, end)
+fn hunk_ranges(ops: &[DiffOp], context: usize) -> Vec<(usize, usize)> {
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffOp::Equal(..)))
+        .map(|(k, _)| k)
+        .collect();
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for k in changes {
+        let start = k.saturating_sub(context);
+        let end = (k + context + 1).min(ops.len());
+        match ranges.last_mut() {
+            // 与上一个 hunk 重叠或相邻时合并
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+    ranges
+}
+
+/// hunk 只有插入时，a 中的位置取它之前最后一个 a 行之后
+fn a_pos_before(ops: &[DiffOp], k: usize) -> usize {
+    ops[..k]
+        .iter()
+        .rev()
+        .find_map(|op| match op {
+            DiffOp::Equal(i, _) | DiffOp::Delete(i) => Some(i + 1),
+            DiffOp::Insert(_) => None,
+        })
+        .unwrap_or(0)
+}
+
+fn b_pos_before(ops: &[DiffOp], k: usize) -> usize {
+    ops[..k]
+        .iter()
+        .rev()
+        .find_map(|op| match op {
+            DiffOp::Equal(_, j) | DiffOp::Insert(j) => Some(j + 1),
+            DiffOp::Delete(_) => None,
+        })
+        .unwrap_or(0)
+}
+
+/// unified diff 的范围写法：长度为 0 时起点是“之前一行”，长度为 1 时省略长度
+fn hunk_header_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, len),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::strings;
+
+    #[test]
+    fn identical_inputs_produce_empty_diff() {
+        let a = strings(&["a", "b"]);
+        assert_eq!(unified_diff(&a, &a, "a", "b", 3), "");
+    }
+
+    #[test]
+    fn unified_diff_matches_diff_u_format() {
+        let a = strings(&["1", "2", "3", "4", "5", "6", "7", "8", "9"]);
+        let b = strings(&["1", "2", "three", "4", "5", "6", "7", "8", "9", "10"]);
+
+        let out = unified_diff(&a, &b, "old", "new", 1);
+        assert_eq!(
+            out,
+            "--- old\n+++ new\n@@ -2,3 +2,3 @@\n 2\n-3\n+three\n 4\n@@ -9 +9,2 @@\n 9\n+10\n"
+        );
+    }
+
+    #[test]
+    fn insert_into_empty_and_delete_all() {
+        let empty: Vec<String> = Vec::new();
+        let b = strings(&["x", "y"]);
+        assert_eq!(unified_diff(&empty, &b, "a", "b", 3), "--- a\n+++ b\n@@ -0,0 +1,2 @@\n+x\n+y\n");
+        assert_eq!(unified_diff(&b, &empty, "a", "b", 3), "--- a\n+++ b\n@@ -1,2 +0,0 @@\n-x\n-y\n");
+    }
+
+    #[test]
+    fn context_zero_keeps_only_changed_lines() {
+        let a = strings(&["1", "2", "3"]);
+        let b = strings(&["1", "two", "3"]);
+        assert_eq!(unified_diff(&a, &b, "a", "b", 0), "--- a\n+++ b\n@@ -2 +2 @@\n-2\n+two\n");
+    }
+
+    #[test]
+    fn colorize_marks_each_line_kind() {
+        let out = colorize("--- a\n+++ b\n@@ -1,3 +1,3 @@\n-x\n+y\n z\n--- sql comment\n+++ counter\n");
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "\x1b[1m--- a\x1b[0m");
+        assert_eq!(lines[2], "\x1b[36m@@ -1,3 +1,3 @@\x1b[0m");
+        assert_eq!(lines[3], "\x1b[31m-x\x1b[0m");
+        assert_eq!(lines[4], "\x1b[32m+y\x1b[0m");
+        assert_eq!(lines[5], " z");
+        // hunk 里以 `---` / `+++` 开头的是普通的删除 / 插入行
+        assert_eq!(lines[6], "\x1b[31m--- sql comment\x1b[0m");
+        assert_eq!(lines[7], "\x1b[32m+++ counter\x1b[0m");
+    }
+}
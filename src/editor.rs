@@ -1,7 +1,7 @@
 //! 文本编辑器：行数组存储内容。
 
-use std::fmt::Write;
-use std::fs;
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
 use std::path::Path;
 
 use crate::{
@@ -10,13 +10,66 @@ use crate::{
         AppError
     },
     commands::doc_command::DocCommand,
+    hooks::SaveHooks,
+    persist::{atomic_write, BackupMode, DiskStamp, Position},
 };
 
+/// 缩进单位：空格或制表符
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndentStyle {
+    Spaces,
+    Tabs,
+}
+
+/// 每个 Editor 独立的缩进设置
+#[derive(Clone, Copy, Debug)]
+pub struct IndentConfig {
+    pub style: IndentStyle,
+    // 制表符宽度，同时也是一级空格缩进的宽度
+    pub width: usize,
+    // append 时沿用上一行的前导空白
+    pub auto: bool,
+}
+
+impl Default for IndentConfig {
+    fn default() -> Self {
+        Self { style: IndentStyle::Spaces, width: 4, auto: false }
+    }
+}
+
+impl IndentConfig {
+    /// 一级缩进对应的字符串
+    pub fn unit(&self) -> String {
+        match self.style {
+            IndentStyle::Spaces => " ".repeat(self.width),
+            IndentStyle::Tabs => "\t".to_string(),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct Editor {
     lines: Vec<String>,
     modified: bool,
     logging: bool,
+    indent: IndentConfig,
+    // 磁盘上的内容是否以换行结尾，保存时原样还原
+    final_newline: bool,
+    // 单个文件的保存钩子设置，None 表示沿用工作区默认
+    save_hooks: Option<SaveHooks>,
+    // 最近一次加载 / 保存时磁盘上的状态；从未落盘时为 None
+    disk: Option<DiskStamp>,
+    // 最近一次 show 的行范围、当前位置与书签；只做记录，编辑时不随内容移动
+    view: Option<(usize, usize)>,
+    position: Option<Position>,
+    bookmarks: BTreeMap<String, Position>,
+    // 只读：Workspace 拒绝对其执行修改和保存。
+    // readonly 是用户要求的（load --readonly），随快照保存；
+    // locked 是每次加载时按查看模式、写权限和有损转换重新算出来的
+    readonly: bool,
+    locked: bool,
+    // 内容版本号，每次 exec/undo/redo/加载都会递增，用于判断是否需要刷新交换文件
+    revision: u64,
 
     // Undo/Redo操作用栈实现。
     undo_stack: Vec<Box<dyn DocCommand>>,
@@ -32,6 +85,7 @@ impl Editor {
         self.undo_stack.push(cmd);
         self.redo_stack.clear();
         self.modified = true;
+        self.revision += 1;
         Ok(())
     }
 
@@ -40,6 +94,7 @@ impl Editor {
             cmd.undo(self)?;
             self.redo_stack.push(cmd);
             self.modified = true;
+            self.revision += 1;
             Ok(())
         } else {
             Err(AppError::InvalidArgs("nothing to undo".into()))
@@ -51,6 +106,7 @@ impl Editor {
             cmd.execute(self)?;
             self.undo_stack.push(cmd);
             self.modified = true;
+            self.revision += 1;
             Ok(())
         } else {
             Err(AppError::InvalidArgs("nothing to redo".into()))
@@ -177,6 +233,58 @@ impl Editor {
         Ok(line_str[start..end].to_string())
     }
 
+    /// 把 `show` 风格的范围落到具体行号：缺省起点为 1，缺省终点为最后一行。
+    /// 与 `show` 不同，这里越界直接报错，供修改类命令使用。
+    pub fn resolve_range(&self, start: Option<usize>, end: Option<usize>) -> AppResult<(usize, usize)> {
+        let n = self.count_lines();
+        if n == 0 {
+            return Err(AppError::InvalidArgs("empty editor: no lines in range".into()));
+        }
+        let s = start.unwrap_or(1);
+        let e = end.unwrap_or(n);
+        if s == 0 || e > n || s > e {
+            return Err(AppError::InvalidArgs(format!(
+                "invalid range: {}..{} (valid 1..={})",
+                s, e, n
+            )));
+        }
+        Ok((s, e))
+    }
+
+    /// 读取 [start, end] 闭区间内的行（行号从 1 起）
+    pub fn lines_range(&self, start: usize, end: usize) -> AppResult<Vec<String>> {
+        self.check_line(start)?;
+        self.check_line(end)?;
+        Ok(self.lines[start - 1..end].to_vec())
+    }
+
+    /// 从第 `at` 行起删除 `remove` 行，并在该位置插入 `new_lines`，返回被删除的行。
+    ///
+    /// 约定：
+    /// - `at` 从 1 起，允许等于 行数+1（即在末尾追加）
+    /// - 删除范围不能超出文档末尾
+    pub fn splice_lines(&mut self, at: usize, remove: usize, new_lines: Vec<String>) -> AppResult<Vec<String>> {
+        let n = self.count_lines();
+        if at == 0 || at > n + 1 {
+            return Err(AppError::InvalidArgs(format!(
+                "line {} out of range (1..={})",
+                at,
+                n + 1
+            )));
+        }
+        let start = at - 1;
+        if start + remove > n {
+            return Err(AppError::InvalidArgs(format!(
+                "splice range [{}..{}) out of {} lines",
+                start, start + remove, n
+            )));
+        }
+
+        let removed = self.lines.splice(start..start + remove, new_lines).collect();
+        self.modified = true;
+        Ok(removed)
+    }
+
     pub fn show(&self, start: usize, end: usize) -> String {
         let mut out = String::new();
         for i in start..=end {
@@ -188,7 +296,9 @@ impl Editor {
 
     pub fn load_from(&mut self, content: &str) {
         self.lines = content.lines().map(|s| s.to_string()).collect();
+        self.final_newline = content.ends_with('\n');
         self.modified = false;
+        self.revision += 1;
         self.logging = self
             .lines
             .first()
@@ -196,15 +306,24 @@ impl Editor {
             .unwrap_or(false);
     }
 
-    // 加问号后，IO异常会被自动转化为AppError，详见error.rs
-    pub fn save_to(&mut self, p: impl AsRef<Path>) -> AppResult<()> {
-        fs::write(p.as_ref(), self.to_string())?;
-        self.modified = false;
-        Ok(())
+    /// 用交换文件里的内容整体替换当前内容，并标记为已修改
+    pub fn restore_lines(&mut self, lines: Vec<String>, final_newline: bool) {
+        self.lines = lines;
+        self.final_newline = final_newline;
+        self.modified = true;
+        self.revision += 1;
     }
 
-    pub fn to_string(&self) -> String {
-        self.lines.join("\n")
+    // 通过临时文件 + rename 原子写入，失败时原文件不受影响
+    pub fn save_to(&mut self, p: impl AsRef<Path>, backup: BackupMode) -> AppResult<()> {
+        let mut data = self.to_string();
+        if self.final_newline && !self.lines.is_empty() {
+            data.push('\n');
+        }
+        atomic_write(p.as_ref(), data.as_bytes(), backup)?;
+        self.disk = DiskStamp::of_file(p.as_ref())?;
+        self.modified = false;
+        Ok(())
     }
 
     // 外部调用函数。
@@ -213,6 +332,36 @@ impl Editor {
     pub fn set_logging(&mut self, logging: bool) { self.logging = logging }
     pub fn is_modified(&self) -> bool { self.modified }
     pub fn logging_enabled(&self) -> bool { self.logging }
+    pub fn has_final_newline(&self) -> bool { self.final_newline }
+    pub fn set_final_newline(&mut self, on: bool) { self.final_newline = on }
+    pub fn save_hooks(&self) -> Option<SaveHooks> { self.save_hooks }
+    pub fn set_save_hooks(&mut self, hooks: Option<SaveHooks>) { self.save_hooks = hooks }
+    pub fn all_lines(&self) -> &[String] { &self.lines }
+    pub fn revision(&self) -> u64 { self.revision }
+    pub fn disk_stamp(&self) -> Option<DiskStamp> { self.disk }
+    pub fn view(&self) -> Option<(usize, usize)> { self.view }
+    pub fn set_view(&mut self, view: Option<(usize, usize)>) { self.view = view }
+    pub fn position(&self) -> Option<Position> { self.position }
+    pub fn set_position(&mut self, pos: Option<Position>) { self.position = pos }
+    pub fn bookmarks(&self) -> &BTreeMap<String, Position> { &self.bookmarks }
+    pub fn set_bookmarks(&mut self, bookmarks: BTreeMap<String, Position>) { self.bookmarks = bookmarks }
+    pub fn set_bookmark(&mut self, name: &str, pos: Position) { self.bookmarks.insert(name.to_string(), pos); }
+    pub fn remove_bookmark(&mut self, name: &str) -> bool { self.bookmarks.remove(name).is_some() }
+    pub fn is_readonly(&self) -> bool { self.readonly || self.locked }
+    pub fn explicit_readonly(&self) -> bool { self.readonly }
+    pub fn set_readonly(&mut self, readonly: bool) { self.readonly = readonly }
+    pub fn set_locked(&mut self, locked: bool) { self.locked = locked }
+    pub fn set_disk_stamp(&mut self, stamp: Option<DiskStamp>) { self.disk = stamp }
+    pub fn indent_config(&self) -> IndentConfig { self.indent }
+    pub fn set_indent_config(&mut self, indent: IndentConfig) { self.indent = indent }
+
+    /// 最后一行的前导空格和制表符，用于自动缩进；与 indent / dedent / retab 对缩进的认定一致
+    pub fn last_line_indent(&self) -> &str {
+        self.lines
+            .last()
+            .map(|l| &l[..l.len() - l.trim_start_matches([' ', '\t']).len()])
+            .unwrap_or("")
+    }
 
     // 辅助函数。
     fn line_mut(&mut self, line: usize) -> AppResult<&mut String> {
@@ -240,6 +389,13 @@ impl Editor {
     fn line_at(&self, idx: usize) -> Option<&str> { self.lines.get(idx).map(|s| s.as_str()) }
 }
 
+// 通过 Display 提供 to_string()：各行以换行拼接。
+impl fmt::Display for Editor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.lines.join("\n"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -300,6 +456,13 @@ mod tests {
         assert_eq!(s, "X");
     }
 
+    #[test]
+    fn last_line_indent_counts_only_spaces_and_tabs() {
+        assert_eq!(editor_with_lines(&["x", "\t  y"]).last_line_indent(), "\t  ");
+        assert_eq!(editor_with_lines(&["  \u{3000}z"]).last_line_indent(), "  ");
+        assert_eq!(Editor::default().last_line_indent(), "");
+    }
+
     #[test]
     fn insert_out_of_range_should_error() {
         let mut ed = editor_with_lines(&["abc"]);
@@ -378,6 +541,37 @@ mod tests {
         assert_eq!(ed.line_ref(3).unwrap(), "l2");
     }
 
+    #[test]
+    fn splice_lines_replaces_and_returns_removed() {
+        let mut ed = editor_with_lines(&["a", "b", "c", "d"]);
+
+        let removed = ed
+            .splice_lines(2, 2, vec!["X".into(), "Y".into(), "Z".into()])
+            .expect("splice_lines failed");
+        assert_eq!(removed, vec!["b", "c"]);
+        assert_eq!(ed.to_string(), "a\nX\nY\nZ\nd");
+
+        // 末尾后一行可以作为插入点
+        ed.splice_lines(6, 0, vec!["e".into()]).expect("append by splice failed");
+        assert_eq!(ed.count_lines(), 6);
+
+        // 越界
+        assert!(ed.splice_lines(8, 0, vec![]).is_err());
+        assert!(ed.splice_lines(5, 3, vec![]).is_err());
+    }
+
+    #[test]
+    fn resolve_range_defaults_and_bounds() {
+        let ed = editor_with_lines(&["a", "b", "c"]);
+
+        assert_eq!(ed.resolve_range(None, None).unwrap(), (1, 3));
+        assert_eq!(ed.resolve_range(Some(2), None).unwrap(), (2, 3));
+        assert_eq!(ed.resolve_range(None, Some(2)).unwrap(), (1, 2));
+        assert!(ed.resolve_range(Some(3), Some(2)).is_err());
+        assert!(ed.resolve_range(Some(1), Some(4)).is_err());
+        assert!(Editor::default().resolve_range(None, None).is_err());
+    }
+
     #[test]
     fn undo_redo_on_empty_stack_should_error() {
         let mut ed = Editor::default();
@@ -19,6 +19,30 @@ pub enum AppError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("Save failed: {0}")]
+    SaveFailed(String),
+
+    #[error("File changed on disk since it was loaded: {0} (use reload, diff-disk or save --force)")]
+    ChangedOnDisk(String),
+
+    #[error("Workspace memento unusable: {0}")]
+    BadMemento(String),
+
+    #[error("Path outside workspace sandbox: {0}")]
+    OutsideSandbox(String),
+
+    #[error("File is read-only: {0}")]
+    ReadOnly(String),
+
+    #[error("Binary file: {0} (use --force to open anyway)")]
+    BinaryFile(String),
+
+    #[error("File too large: {0} (use --force to open anyway)")]
+    FileTooLarge(String),
+
+    #[error("External command failed: {0}")]
+    ExternalCommand(String),
+
     #[error("Internal error: {0}")]
     InternalError(String),
 }
@@ -33,6 +57,14 @@ impl AppError {
             AppError::InvalidCommand(_) => 1003,
             AppError::Io(_)             => 2001,
             AppError::Json(_)           => 2002,
+            AppError::ExternalCommand(_) => 2003,
+            AppError::SaveFailed(_)     => 2004,
+            AppError::ChangedOnDisk(_)  => 2005,
+            AppError::BadMemento(_)     => 2006,
+            AppError::OutsideSandbox(_) => 2007,
+            AppError::ReadOnly(_)       => 2008,
+            AppError::BinaryFile(_)     => 2009,
+            AppError::FileTooLarge(_)   => 2010,
             AppError::InternalError(_)  => 3001,
         }
     }
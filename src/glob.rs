@@ -0,0 +1,281 @@
+//! 通配符匹配与 .gitignore 规则。
+//! 路径一律用 `/` 分隔、相对于某个目录。支持：
+//! - `?` 任意一个字符，`*` 任意多个字符，都不跨越 `/`
+//! - `**/` 零个或多个目录，末尾的 `**` 匹配其下的一切
+//! - `[abc]`、`[a-z]`、`[!a-z]` 字符类
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use crate::error::{AppError, AppResult};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Tok {
+    Char(char),
+    Any,
+    Star,
+    /// `**`：任意字符，可以跨越 `/`
+    DoubleStar,
+    /// `**/`：空串，或以 `/` 结尾的任意字符串
+    DirStar,
+    Class { negated: bool, ranges: Vec<(char, char)> },
+}
+
+#[derive(Clone, Debug)]
+pub struct Glob {
+    toks: Vec<Tok>,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> AppResult<Self> {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut toks = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '?' => toks.push(Tok::Any),
+                '*' if chars.get(i + 1) == Some(&'*') => {
+                    i += 1;
+                    if chars.get(i + 1) == Some(&'/') {
+                        i += 1;
+                        toks.push(Tok::DirStar);
+                    } else {
+                        toks.push(Tok::DoubleStar);
+                    }
+                }
+                '*' => toks.push(Tok::Star),
+                '[' => {
+                    let end = (i + 2..chars.len())
+                        .find(|&j| chars[j] == ']')
+                        .ok_or_else(|| AppError::InvalidArgs(format!("unclosed [ in pattern {}", pattern)))?;
+                    let mut body = &chars[i + 1..end];
+                    let negated = matches!(body.first(), Some('!' | '^'));
+                    if negated {
+                        body = &body[1..];
+                    }
+                    let mut ranges = Vec::new();
+                    let mut k = 0;
+                    while k < body.len() {
+                        if body.get(k + 1) == Some(&'-') && k + 2 < body.len() {
+                            ranges.push((body[k], body[k + 2]));
+                            k += 3;
+                        } else {
+                            ranges.push((body[k], body[k]));
+                            k += 1;
+                        }
+                    }
+                    toks.push(Tok::Class { negated, ranges });
+                    i = end;
+                }
+                '\\' if i + 1 < chars.len() => {
+                    i += 1;
+                    toks.push(Tok::Char(chars[i]));
+                }
+                c => toks.push(Tok::Char(c)),
+            }
+            i += 1;
+        }
+        Ok(Self { toks })
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        let chars: Vec<char> = path.chars().collect();
+        match_here(&self.toks, &chars)
+    }
+}
+
+/// 字符串里是否有通配符
+pub fn has_wildcards(s: &str) -> bool {
+    s.contains(['*', '?', '['])
+}
+
+/// 在磁盘上展开模式，返回排好序的文件路径。相对模式以 base 为起点，绝对模式直接使用。
+/// 从模式开头不含通配符的那几级目录开始遍历，不进入符号链接目录；
+/// 以 `.` 开头的文件和目录只有模式里也写了以 `.` 开头的一段时才参与匹配。
+pub fn expand(base: &Path, pattern: &str) -> AppResult<Vec<PathBuf>> {
+    let (root, rest) = split_root(base, pattern);
+    if rest.is_empty() {
+        return Ok(if root.is_file() { vec![root] } else { Vec::new() });
+    }
+
+    let walk = Walk {
+        glob: Glob::new(&rest)?,
+        hidden: rest.split('/').any(|seg| seg.starts_with('.')),
+        max_depth: if rest.contains("**") { None } else { Some(rest.split('/').count()) },
+    };
+    let mut found = Vec::new();
+    walk.dir(&root, "", 1, &mut found);
+    found.sort();
+    Ok(found.into_iter().map(|rel| root.join(rel)).collect())
+}
+
+/// 展开模式时开始遍历的目录：模式开头不含通配符的那几级
+pub fn literal_root(base: &Path, pattern: &str) -> PathBuf {
+    split_root(base, pattern).0
+}
+
+/// 拆成起点目录和其后带通配符的部分
+fn split_root(base: &Path, pattern: &str) -> (PathBuf, String) {
+    let parts: Vec<&str> = pattern.split('/').collect();
+    let n = parts.iter().position(|p| has_wildcards(p)).unwrap_or(parts.len());
+    let root = match parts[..n].join("/").as_str() {
+        "" if pattern.starts_with('/') => PathBuf::from("/"),
+        "" => base.to_path_buf(),
+        p => base.join(p),
+    };
+    (root, parts[n..].join("/"))
+}
+
+struct Walk {
+    glob: Glob,
+    hidden: bool,
+    max_depth: Option<usize>,
+}
+
+impl Walk {
+    /// 读不了的目录跳过
+    fn dir(&self, dir: &Path, rel: &str, depth: usize, found: &mut Vec<String>) {
+        let Ok(entries) = fs::read_dir(dir) else { return };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') && !self.hidden {
+                continue;
+            }
+            let child_rel = if rel.is_empty() { name } else { format!("{}/{}", rel, name) };
+            let path = entry.path();
+            let Ok(meta) = fs::symlink_metadata(&path) else { continue };
+            if meta.is_dir() {
+                if self.max_depth.is_none_or(|d| depth < d) {
+                    self.dir(&path, &child_rel, depth + 1, found);
+                }
+            } else if path.is_file() && self.glob.matches(&child_rel) {
+                found.push(child_rel);
+            }
+        }
+    }
+}
+
+fn match_here(toks: &[Tok], s: &[char]) -> bool {
+    let Some((tok, rest)) = toks.split_first() else { return s.is_empty() };
+    match tok {
+        Tok::Char(c) => s.first() == Some(c) && match_here(rest, &s[1..]),
+        Tok::Any => s.first().is_some_and(|&c| c != '/') && match_here(rest, &s[1..]),
+        Tok::Class { negated, ranges } => s.first().is_some_and(|&c| {
+            c != '/' && ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi) != *negated
+        }) && match_here(rest, &s[1..]),
+        Tok::Star => {
+            let limit = s.iter().position(|&c| c == '/').unwrap_or(s.len());
+            (0..=limit).any(|i| match_here(rest, &s[i..]))
+        }
+        Tok::DoubleStar => (0..=s.len()).any(|i| match_here(rest, &s[i..])),
+        Tok::DirStar => {
+            match_here(rest, s)
+                || s.iter().enumerate().any(|(i, &c)| c == '/' && match_here(rest, &s[i + 1..]))
+        }
+    }
+}
+
+/// 一个 .gitignore 文件里的规则
+#[derive(Clone, Debug, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Clone, Debug)]
+struct IgnoreRule {
+    glob: Glob,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl IgnoreRules {
+    /// 无法解析的行跳过
+    pub fn parse(text: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in text.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            // 不含 `/` 的规则匹配任意深度的名字，含 `/` 的相对于 .gitignore 所在目录
+            let pattern = match line.strip_prefix('/') {
+                Some(anchored) => anchored.to_string(),
+                None if line.contains('/') => line.to_string(),
+                None => format!("**/{}", line),
+            };
+            if let Ok(glob) = Glob::new(&pattern) {
+                rules.push(IgnoreRule { glob, negated, dir_only });
+            }
+        }
+        Self { rules }
+    }
+
+    /// 按最后一条匹配的规则判断：Some(true) 忽略，Some(false) 被 `!` 重新包含，None 没有规则匹配
+    pub fn check(&self, rel: &str, is_dir: bool) -> Option<bool> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|r| (is_dir || !r.dir_only) && r.glob.matches(rel))
+            .map(|r| !r.negated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards_respect_path_separators() {
+        let g = |p: &str| Glob::new(p).unwrap();
+        assert!(g("*.rs").matches("main.rs"));
+        assert!(!g("*.rs").matches("src/main.rs"));
+        assert!(g("src/**/*.rs").matches("src/main.rs"));
+        assert!(g("src/**/*.rs").matches("src/a/b/c.rs"));
+        assert!(!g("src/**/*.rs").matches("tests/a.rs"));
+        assert!(g("target/**").matches("target/debug/x"));
+        assert!(g("file?.[a-c]x").matches("file1.bx"));
+        assert!(!g("file?.[!a-c]x").matches("file1.bx"));
+        assert!(Glob::new("[abc").is_err());
+        assert!(has_wildcards("src/*.rs") && !has_wildcards("src/main.rs"));
+    }
+
+    #[test]
+    fn expand_walks_from_literal_prefix_and_skips_hidden() {
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path();
+        for f in ["src/main.rs", "src/a/b.rs", "src/a/c.txt", "src/.hidden/d.rs", "top.rs"] {
+            let p = base.join(f);
+            fs::create_dir_all(p.parent().unwrap()).unwrap();
+            fs::write(p, "x").unwrap();
+        }
+        let rel = |v: Vec<PathBuf>| -> Vec<String> {
+            v.iter().map(|p| p.strip_prefix(base).unwrap().to_string_lossy().into_owned()).collect()
+        };
+        assert_eq!(rel(expand(base, "src/**/*.rs").unwrap()), ["src/a/b.rs", "src/main.rs"]);
+        assert_eq!(rel(expand(base, "*.rs").unwrap()), ["top.rs"]);
+        assert_eq!(rel(expand(base, "src/.*/*.rs").unwrap()), ["src/.hidden/d.rs"]);
+        assert!(expand(base, "nope/*.rs").unwrap().is_empty());
+        assert_eq!(literal_root(base, "src/a/*.rs"), base.join("src/a"));
+        assert_eq!(literal_root(base, "/etc/**/*.conf"), Path::new("/etc"));
+    }
+
+    #[test]
+    fn gitignore_rules_use_last_match() {
+        let rules = IgnoreRules::parse("# build\ntarget/\n*.log\n!keep.log\n/root.txt\n");
+        assert_eq!(rules.check("target", true), Some(true));
+        assert_eq!(rules.check("target", false), None);
+        assert_eq!(rules.check("a/b/x.log", false), Some(true));
+        assert_eq!(rules.check("a/keep.log", false), Some(false));
+        assert_eq!(rules.check("root.txt", false), Some(true));
+        assert_eq!(rules.check("sub/root.txt", false), None);
+    }
+}
@@ -0,0 +1,167 @@
+//! 本地历史：每次保存时把写到磁盘的内容压缩存一份快照。
+//! 快照放在 `base_dir/.history/<文件名>-<路径哈希>/<时间戳>.gz`，
+//! 时间戳即快照 id，按字典序排列就是时间顺序。
+
+use std::{
+    fs,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use chrono::Local;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use crate::{
+    error::{AppError, AppResult},
+    persist::{atomic_write, fnv1a, BackupMode},
+};
+
+pub const HISTORY_DIR: &str = ".history";
+
+/// 快照保留策略：数量和时间两个上限可以同时生效
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Retention {
+    pub max_count: Option<usize>,
+    pub max_age: Option<Duration>,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self { max_count: Some(50), max_age: None }
+    }
+}
+
+impl Retention {
+    pub fn describe(&self) -> String {
+        let count = self.max_count.map_or("unlimited".to_string(), |n| n.to_string());
+        let age = self
+            .max_age
+            .map_or("unlimited".to_string(), |d| format!("{}d", d.as_secs() / 86_400));
+        format!("count: {}\nage: {}", count, age)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub id: String,
+    pub path: PathBuf,
+    /// 压缩后的大小
+    pub size: u64,
+    pub saved_at: SystemTime,
+}
+
+/// 某个文件的快照目录
+pub fn dir_for(base_dir: &Path, file: &Path) -> PathBuf {
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    let hash = fnv1a(file.to_string_lossy().as_bytes());
+    base_dir.join(HISTORY_DIR).join(format!("{}-{:08x}", name, hash as u32))
+}
+
+/// 记录一份快照；与最近一份内容相同时不重复记录，返回 None
+pub fn record(base_dir: &Path, file: &Path, data: &[u8]) -> AppResult<Option<String>> {
+    let dir = dir_for(base_dir, file);
+    if let Some(latest) = list(base_dir, file)?.first()
+        && read_snapshot(&latest.path)? == data
+    {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(&dir)?;
+    let stamp = Local::now().format("%Y%m%d-%H%M%S-%3f").to_string();
+    // 同一毫秒内多次保存时加序号区分
+    let mut id = stamp.clone();
+    let mut n = 1;
+    while dir.join(format!("{}.gz", id)).exists() {
+        id = format!("{}-{}", stamp, n);
+        n += 1;
+    }
+
+    let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+    enc.write_all(data)?;
+    atomic_write(&dir.join(format!("{}.gz", id)), &enc.finish()?, BackupMode::Off)?;
+    Ok(Some(id))
+}
+
+/// 列出某个文件的全部快照，最新的在前
+pub fn list(base_dir: &Path, file: &Path) -> AppResult<Vec<Snapshot>> {
+    let Ok(entries) = fs::read_dir(dir_for(base_dir, file)) else { return Ok(Vec::new()) };
+    let mut snaps = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "gz") {
+            continue;
+        }
+        let meta = entry.metadata()?;
+        snaps.push(Snapshot {
+            id: path.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+            size: meta.len(),
+            saved_at: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            path,
+        });
+    }
+    snaps.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(snaps)
+}
+
+/// 按 id 查找快照；纯数字时按列表序号（1 为最新）
+pub fn find(base_dir: &Path, file: &Path, id: &str) -> AppResult<Snapshot> {
+    let snaps = list(base_dir, file)?;
+    let found = if !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()) && id.len() < 8 {
+        id.parse::<usize>().ok().and_then(|n| n.checked_sub(1)).and_then(|i| snaps.get(i))
+    } else {
+        snaps.iter().find(|s| s.id == id)
+    };
+    found
+        .cloned()
+        .ok_or_else(|| AppError::InvalidArgs(format!("no history entry {} for {}", id, file.display())))
+}
+
+pub fn read_snapshot(path: &Path) -> AppResult<Vec<u8>> {
+    let mut data = Vec::new();
+    GzDecoder::new(fs::File::open(path)?).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// 按保留策略删除旧快照，返回删除的数量。最新的一份总是保留。
+pub fn prune(base_dir: &Path, file: &Path, retention: Retention) -> AppResult<usize> {
+    let now = SystemTime::now();
+    let mut removed = 0;
+    for (n, snap) in list(base_dir, file)?.iter().enumerate().skip(1) {
+        let too_many = retention.max_count.is_some_and(|max| n >= max);
+        let too_old = retention
+            .max_age
+            .is_some_and(|age| now.duration_since(snap.saved_at).unwrap_or_default() > age);
+        if too_many || too_old {
+            fs::remove_file(&snap.path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_dedupes_and_prunes_by_count() {
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path();
+        let file = base.join("a.txt");
+
+        let first = record(base, &file, b"one\n").unwrap().unwrap();
+        assert!(record(base, &file, b"one\n").unwrap().is_none());
+        record(base, &file, b"two\n").unwrap().unwrap();
+        record(base, &file, b"three\n").unwrap().unwrap();
+
+        let snaps = list(base, &file).unwrap();
+        assert_eq!(snaps.len(), 3);
+        assert_eq!(read_snapshot(&snaps[0].path).unwrap(), b"three\n");
+        assert_eq!(find(base, &file, "3").unwrap().id, first);
+        assert_eq!(find(base, &file, &first).unwrap().id, first);
+        assert!(find(base, &file, "4").is_err());
+
+        let keep_two = Retention { max_count: Some(2), max_age: None };
+        assert_eq!(prune(base, &file, keep_two).unwrap(), 1);
+        assert!(find(base, &file, &first).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+//! 保存前的空白整理钩子。
+//! 由 Workspace 在写盘前调用，整理结果作为一条 DocCommand 进入撤销栈，
+//! 保证内存中的内容与写到磁盘上的一致。
+
+use crate::{
+    commands::doc_command::ReplaceContentCommand,
+    editor::Editor,
+    error::{AppError, AppResult},
+};
+
+/// 各项钩子可单独开关；工作区有一份默认值，单个文件可以覆盖。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SaveHooks {
+    /// 去掉行尾空白
+    pub strip_trailing: bool,
+    /// 连续多个空行合并为一个
+    pub collapse_blank: bool,
+    /// 保证文件以换行结尾
+    pub final_newline: bool,
+}
+
+impl SaveHooks {
+    pub const NAMES: [&'static str; 3] = ["strip-trailing", "collapse-blank", "final-newline"];
+
+    pub fn set(&mut self, name: &str, on: bool) -> AppResult<()> {
+        match name {
+            "strip-trailing" => self.strip_trailing = on,
+            "collapse-blank" => self.collapse_blank = on,
+            "final-newline" => self.final_newline = on,
+            other => {
+                return Err(AppError::InvalidArgs(format!(
+                    "unknown save hook: {} (expected one of {})",
+                    other,
+                    Self::NAMES.join(", ")
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn describe(&self) -> String {
+        let flag = |b: bool| if b { "on" } else { "off" };
+        format!(
+            "strip-trailing: {}\ncollapse-blank: {}\nfinal-newline: {}",
+            flag(self.strip_trailing),
+            flag(self.collapse_blank),
+            flag(self.final_newline)
+        )
+    }
+
+    /// 按开启的钩子整理文本，返回整理后的行
+    pub fn clean(&self, lines: &[String]) -> Vec<String> {
+        let mut out: Vec<String> = Vec::with_capacity(lines.len());
+        for line in lines {
+            let line = if self.strip_trailing { line.trim_end() } else { line.as_str() };
+            if self.collapse_blank
+                && line.trim().is_empty()
+                && out.last().is_some_and(|prev| prev.trim().is_empty())
+            {
+                continue;
+            }
+            out.push(line.to_string());
+        }
+        out
+    }
+
+    /// 生成整理命令；内容无需改动时返回 None，避免往撤销栈里塞空操作
+    pub fn cleanup_for(&self, ed: &Editor) -> Option<ReplaceContentCommand> {
+        let lines = ed.all_lines();
+        let new_lines = self.clean(lines);
+        let final_newline = ed.has_final_newline() || self.final_newline;
+        if new_lines == lines && final_newline == ed.has_final_newline() {
+            return None;
+        }
+        Some(ReplaceContentCommand::new(new_lines, final_newline))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::strings;
+
+    #[test]
+    fn clean_applies_only_enabled_hooks() {
+        let lines = strings(&["a  ", "", "  ", "", "b\t"]);
+
+        assert_eq!(SaveHooks::default().clean(&lines), lines);
+
+        let strip = SaveHooks { strip_trailing: true, ..Default::default() };
+        assert_eq!(strip.clean(&lines), strings(&["a", "", "", "", "b"]));
+
+        let collapse = SaveHooks { collapse_blank: true, ..Default::default() };
+        assert_eq!(collapse.clean(&lines), strings(&["a  ", "", "b\t"]));
+    }
+
+    #[test]
+    fn cleanup_is_undoable_and_skipped_when_clean() {
+        let mut ed = Editor::new();
+        ed.load_from("x  \ny");
+
+        let hooks = SaveHooks { strip_trailing: true, final_newline: true, ..Default::default() };
+        let cmd = hooks.cleanup_for(&ed).expect("cleanup expected");
+        ed.exec_doc(Box::new(cmd)).unwrap();
+        assert_eq!(ed.all_lines(), strings(&["x", "y"]));
+        assert!(ed.has_final_newline());
+        assert!(hooks.cleanup_for(&ed).is_none());
+
+        ed.undo().unwrap();
+        assert_eq!(ed.all_lines(), strings(&["x  ", "y"]));
+        assert!(!ed.has_final_newline());
+    }
+}
@@ -4,26 +4,28 @@ use std::{
     collections::HashSet,
     fs::OpenOptions,
     io::Write,
-    path::{PathBuf},
+    path::{Path, PathBuf},
 };
 use chrono::Local;
 use crate::event::{Event, Subscriber};
 
+/// 文件的日志放在 base_dir 下，名字基于文件名：`.{文件名}.log`
+pub fn log_path_for(base_dir: &Path, file: &Path) -> PathBuf {
+    let file_name = file.file_name().unwrap_or_default().to_string_lossy();
+    base_dir.join(format!(".{}.log", file_name))
+}
+
 pub struct Logger {
     written: HashSet<PathBuf>,
     base_dir: PathBuf,
 }
 
 impl Logger {
-    pub fn new(base_dir: PathBuf) -> Self { Self { written: HashSet::new(), base_dir: base_dir } }
+    pub fn new(base_dir: PathBuf) -> Self { Self { written: HashSet::new(), base_dir } }
 
     fn logfile_for(&self, path: &Option<PathBuf>) -> PathBuf {
         match path {
-            Some(p) => {
-                // 文件日志：放在 base_dir 下，名字基于文件名
-                let file_name = p.file_name().unwrap_or_default().to_string_lossy();
-                self.base_dir.join(format!(".{}.log", file_name))
-            }
+            Some(p) => log_path_for(&self.base_dir, p),
             None => {
                 // app 级别日志：work_dir/.app.log
                 self.base_dir.join(".app.log")
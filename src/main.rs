@@ -1,20 +1,38 @@
 mod application;
 // mod command;
+mod diff;
 mod editor;
 mod error;
 mod event;
+mod glob;
+mod history;
+mod hooks;
 mod logging;
+mod merge;
 mod outcome;
+mod patch;
 mod persist;
 mod router;
+mod sandbox;
+mod swap;
+#[cfg(test)]
+mod test_util;
+mod trash;
 mod workspace;
 mod commands;
 
-use crate::application::Application;
+use crate::application::{Application, StartOptions};
 use crate::error::AppResult;
 
 fn main() -> AppResult<()> {
-    let mut app = Application::new()?;
+    let opts = match StartOptions::from_args(std::env::args().skip(1)) {
+        Ok(o) => o,
+        Err(e) => {
+            e.report();
+            std::process::exit(2);
+        }
+    };
+    let mut app = Application::new(&opts)?;
     if let Err(e) = app.run() {
         e.report();
     }
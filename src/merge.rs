@@ -0,0 +1,192 @@
+//! 按行三方合并与冲突标记处理。
+//! 合并算法与 diff3 相同：先分别求 base→ours、base→theirs 的编辑序列，
+//! 两边都保持不变的 base 行作为锚点，锚点之间的片段按“只有一边改了就取那一边”合并，
+//! 两边改得不一样时输出冲突标记。
+
+use crate::diff::{diff_lines, DiffOp};
+
+pub const MARKER_OURS: &str = "<<<<<<<";
+pub const MARKER_BASE: &str = "|||||||";
+pub const MARKER_SEP: &str = "=======";
+pub const MARKER_THEIRS: &str = ">>>>>>>";
+
+pub struct MergeResult {
+    pub lines: Vec<String>,
+    pub conflicts: usize,
+}
+
+/// 三方合并；冲突块的标记行带上 ours / theirs 的标签
+pub fn merge3(
+    base: &[String],
+    ours: &[String],
+    theirs: &[String],
+    ours_label: &str,
+    theirs_label: &str,
+) -> MergeResult {
+    let to_ours = matches(base.len(), &diff_lines(base, ours));
+    let to_theirs = matches(base.len(), &diff_lines(base, theirs));
+
+    let mut lines = Vec::new();
+    let mut conflicts = 0;
+    let (mut i, mut j, mut k) = (0, 0, 0);
+
+    loop {
+        // 下一个两边都没动的 base 行；没有时以三者的末尾为锚点
+        let anchor = (i..base.len()).find(|&o| to_ours[o].is_some() && to_theirs[o].is_some());
+        let (o, oj, ok) = match anchor {
+            Some(o) => (o, to_ours[o].unwrap(), to_theirs[o].unwrap()),
+            None => (base.len(), ours.len(), theirs.len()),
+        };
+
+        if (o, oj, ok) != (i, j, k) {
+            let (b, x, y) = (&base[i..o], &ours[j..oj], &theirs[k..ok]);
+            if x == b || x == y {
+                lines.extend_from_slice(y);
+            } else if y == b {
+                lines.extend_from_slice(x);
+            } else {
+                conflicts += 1;
+                lines.push(format!("{} {}", MARKER_OURS, ours_label));
+                lines.extend_from_slice(x);
+                lines.push(MARKER_SEP.to_string());
+                lines.extend_from_slice(y);
+                lines.push(format!("{} {}", MARKER_THEIRS, theirs_label));
+            }
+        }
+
+        if anchor.is_none() {
+            break;
+        }
+        lines.push(base[o].clone());
+        (i, j, k) = (o + 1, oj + 1, ok + 1);
+    }
+
+    MergeResult { lines, conflicts }
+}
+
+/// base 中每一行在另一侧对应的行号；被删除或改动的为 None
+fn matches(base_len: usize, ops: &[DiffOp]) -> Vec<Option<usize>> {
+    let mut map = vec![None; base_len];
+    for op in ops {
+        if let DiffOp::Equal(i, j) = op {
+            map[*i] = Some(*j);
+        }
+    }
+    map
+}
+
+/// 冲突解决方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Ours,
+    Theirs,
+    Both,
+}
+
+impl Side {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "ours" => Some(Side::Ours),
+            "theirs" => Some(Side::Theirs),
+            "both" => Some(Side::Both),
+            _ => None,
+        }
+    }
+}
+
+/// 一个冲突块中各标记行的位置（从 0 起）；base 段只在 diff3 风格的标记中出现
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub start: usize,
+    pub base: Option<usize>,
+    pub sep: usize,
+    pub end: usize,
+}
+
+impl Conflict {
+    pub fn ours<'a>(&self, lines: &'a [String]) -> &'a [String] {
+        &lines[self.start + 1..self.base.unwrap_or(self.sep)]
+    }
+
+    pub fn theirs<'a>(&self, lines: &'a [String]) -> &'a [String] {
+        &lines[self.sep + 1..self.end]
+    }
+
+    /// 按选择的一侧生成替换 start..=end 的内容
+    pub fn resolve(&self, lines: &[String], side: Side) -> Vec<String> {
+        match side {
+            Side::Ours => self.ours(lines).to_vec(),
+            Side::Theirs => self.theirs(lines).to_vec(),
+            Side::Both => [self.ours(lines), self.theirs(lines)].concat(),
+        }
+    }
+}
+
+/// 扫描文本中的冲突块；不完整的标记忽略
+pub fn find_conflicts(lines: &[String]) -> Vec<Conflict> {
+    let is = |line: &String, marker: &str| {
+        line.strip_prefix(marker).is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+    };
+
+    let mut found = Vec::new();
+    let mut cur: Option<Conflict> = None;
+    for (n, line) in lines.iter().enumerate() {
+        if is(line, MARKER_OURS) {
+            cur = Some(Conflict { start: n, base: None, sep: 0, end: 0 });
+        } else if let Some(c) = cur.as_mut() {
+            if is(line, MARKER_BASE) && c.base.is_none() && c.sep == 0 {
+                c.base = Some(n);
+            } else if line == MARKER_SEP && c.sep == 0 {
+                c.sep = n;
+            } else if is(line, MARKER_THEIRS) && c.sep != 0 {
+                c.end = n;
+                found.push(*c);
+                cur = None;
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::strings;
+
+    #[test]
+    fn non_overlapping_changes_merge_cleanly() {
+        let base = strings(&["a", "b", "c", "d", "e"]);
+        let ours = strings(&["a", "B", "c", "d", "e"]);
+        let theirs = strings(&["a", "b", "c", "d", "E", "f"]);
+        let m = merge3(&base, &ours, &theirs, "ours", "theirs");
+        assert_eq!(m.conflicts, 0);
+        assert_eq!(m.lines, strings(&["a", "B", "c", "d", "E", "f"]));
+    }
+
+    #[test]
+    fn overlapping_changes_produce_markers_and_resolve() {
+        let base = strings(&["a", "b", "c"]);
+        let ours = strings(&["a", "ours", "c"]);
+        let theirs = strings(&["a", "theirs", "c"]);
+        let m = merge3(&base, &ours, &theirs, "mine.txt", "other.txt");
+        assert_eq!(m.conflicts, 1);
+        assert_eq!(
+            m.lines,
+            strings(&["a", "<<<<<<< mine.txt", "ours", "=======", "theirs", ">>>>>>> other.txt", "c"])
+        );
+
+        let conflicts = find_conflicts(&m.lines);
+        assert_eq!(conflicts, vec![Conflict { start: 1, base: None, sep: 3, end: 5 }]);
+        assert_eq!(conflicts[0].resolve(&m.lines, Side::Theirs), strings(&["theirs"]));
+        assert_eq!(conflicts[0].resolve(&m.lines, Side::Both), strings(&["ours", "theirs"]));
+    }
+
+    #[test]
+    fn diff3_style_markers_are_recognised() {
+        let lines = strings(&["<<<<<<< HEAD", "x", "||||||| base", "o", "=======", "y", ">>>>>>> branch"]);
+        let c = find_conflicts(&lines);
+        assert_eq!(c.len(), 1);
+        assert_eq!(c[0].resolve(&lines, Side::Ours), strings(&["x"]));
+        assert_eq!(c[0].resolve(&lines, Side::Theirs), strings(&["y"]));
+    }
+}
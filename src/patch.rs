@@ -0,0 +1,282 @@
+//! 解析 unified diff 补丁，并把其中的 hunk 应用到文本行上。
+//! 与 GNU patch 类似，hunk 找不到原位置时会在附近搜索（offset），
+//! 仍找不到时逐级忽略首尾的上下文行再试（fuzz）。
+
+use crate::error::{AppError, AppResult};
+
+/// hunk 中的一行
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hunk {
+    /// 原文件中的起始行号（1 起；长度为 0 时表示“在这一行之后”）
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+/// 补丁中针对单个文件的部分
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FilePatch {
+    pub old_path: String,
+    pub new_path: String,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    /// 补丁作用的文件：一般取新路径，删除文件时（新路径为 /dev/null）取旧路径。
+    /// strip 表示去掉路径开头的几级目录，与 patch -p 相同。
+    pub fn target(&self, strip: usize) -> String {
+        let path = if self.new_path == "/dev/null" { &self.old_path } else { &self.new_path };
+        path.split('/').skip(strip).collect::<Vec<_>>().join("/")
+    }
+
+    /// 两边路径都是 git 风格的 a/ b/ 前缀时，默认应去掉一级
+    pub fn default_strip(&self) -> usize {
+        let prefixed = |p: &str, pre: &str| p == "/dev/null" || p.starts_with(pre);
+        if prefixed(&self.old_path, "a/") && prefixed(&self.new_path, "b/") { 1 } else { 0 }
+    }
+}
+
+/// 单个 hunk 的应用结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HunkResult {
+    /// offset 为实际位置与标注位置之差，fuzz 为忽略的上下文行数
+    Applied { offset: isize, fuzz: usize },
+    Rejected,
+}
+
+/// 解析补丁文本。`diff --git`、`index` 等不认识的行直接跳过。
+pub fn parse_patch(text: &str) -> AppResult<Vec<FilePatch>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut k = 0;
+
+    while k < lines.len() {
+        let line = lines[k];
+        if let Some(old) = line.strip_prefix("--- ")
+            && let Some(new) = lines.get(k + 1).and_then(|l| l.strip_prefix("+++ "))
+        {
+            files.push(FilePatch {
+                old_path: header_path(old),
+                new_path: header_path(new),
+                hunks: Vec::new(),
+            });
+            k += 2;
+            continue;
+        }
+
+        if line.starts_with("@@") {
+            let file = files
+                .last_mut()
+                .ok_or_else(|| AppError::InvalidArgs(format!("line {}: hunk before file header", k + 1)))?;
+            let (old_start, old_len, new_start, new_len) = parse_hunk_header(line)
+                .ok_or_else(|| AppError::InvalidArgs(format!("line {}: bad hunk header: {}", k + 1, line)))?;
+            k += 1;
+
+            let (mut old_seen, mut new_seen) = (0, 0);
+            let mut body = Vec::new();
+            while (old_seen < old_len || new_seen < new_len) && k < lines.len() {
+                let l = lines[k];
+                k += 1;
+                // 行尾换行的标记不影响按行比较
+                if l.starts_with('\\') {
+                    continue;
+                }
+                match l.chars().next() {
+                    Some('+') => {
+                        body.push(HunkLine::Add(l[1..].to_string()));
+                        new_seen += 1;
+                    }
+                    Some('-') => {
+                        body.push(HunkLine::Remove(l[1..].to_string()));
+                        old_seen += 1;
+                    }
+                    // 有些工具会把空的上下文行的前导空格去掉
+                    Some(' ') | None => {
+                        body.push(HunkLine::Context(l.get(1..).unwrap_or("").to_string()));
+                        old_seen += 1;
+                        new_seen += 1;
+                    }
+                    Some(_) => {
+                        return Err(AppError::InvalidArgs(format!("line {}: unexpected line in hunk: {}", k, l)));
+                    }
+                }
+            }
+            if old_seen != old_len || new_seen != new_len {
+                return Err(AppError::InvalidArgs(format!("truncated hunk in {}", file.new_path)));
+            }
+            file.hunks.push(Hunk { old_start, old_len, new_start, new_len, lines: body });
+            continue;
+        }
+
+        k += 1;
+    }
+
+    if files.is_empty() {
+        return Err(AppError::InvalidArgs("no file headers found in patch".into()));
+    }
+    Ok(files)
+}
+
+/// 去掉头部路径后面的时间戳（以制表符分隔）
+fn header_path(s: &str) -> String {
+    s.split('\t').next().unwrap_or("").trim_end().to_string()
+}
+
+/// 解析 `@@ -a,b +c,d @@`，省略的长度为 1
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize, usize)> {
+    let inner = line.strip_prefix("@@ ")?;
+    let inner = &inner[..inner.find(" @@")?];
+    let (old, new) = inner.split_once(' ')?;
+    let range = |s: &str| -> Option<(usize, usize)> {
+        match s.split_once(',') {
+            Some((a, b)) => Some((a.parse().ok()?, b.parse().ok()?)),
+            None => Some((s.parse().ok()?, 1)),
+        }
+    };
+    let (a, b) = range(old.strip_prefix('-')?)?;
+    let (c, d) = range(new.strip_prefix('+')?)?;
+    Some((a, b, c, d))
+}
+
+/// 依次应用 hunk，返回结果行与每个 hunk 的结果。被拒绝的 hunk 不影响其余 hunk。
+pub fn apply_hunks(lines: &[String], hunks: &[Hunk], max_fuzz: usize) -> (Vec<String>, Vec<HunkResult>) {
+    let mut out: Vec<String> = lines.to_vec();
+    let mut results = Vec::with_capacity(hunks.len());
+    // 已应用的 hunk 带来的行数变化，以及上一个 hunk 的偏移
+    let mut delta: isize = 0;
+    let mut last_offset: isize = 0;
+    // 之后的 hunk 不能再匹配到已改动区域之前
+    let mut min_pos = 0;
+
+    for hunk in hunks {
+        let base = if hunk.old_len == 0 { hunk.old_start } else { hunk.old_start.saturating_sub(1) };
+        let mut result = HunkResult::Rejected;
+
+        for fuzz in 0..=max_fuzz {
+            let (skip_head, skip_tail) = context_trim(&hunk.lines, fuzz);
+            let body = &hunk.lines[skip_head..hunk.lines.len() - skip_tail];
+            let old: Vec<&String> = body
+                .iter()
+                .filter_map(|l| match l {
+                    HunkLine::Context(s) | HunkLine::Remove(s) => Some(s),
+                    HunkLine::Add(_) => None,
+                })
+                .collect();
+            let expected = base as isize + delta + last_offset + skip_head as isize;
+
+            if let Some(pos) = find_match(&out, &old, expected, min_pos) {
+                let new: Vec<String> = body
+                    .iter()
+                    .filter_map(|l| match l {
+                        HunkLine::Context(s) | HunkLine::Add(s) => Some(s.clone()),
+                        HunkLine::Remove(_) => None,
+                    })
+                    .collect();
+                let new_len = new.len();
+                out.splice(pos..pos + old.len(), new);
+
+                let offset = pos as isize - (base as isize + delta + skip_head as isize);
+                delta += new_len as isize - old.len() as isize;
+                last_offset = offset;
+                min_pos = pos + new_len;
+                result = HunkResult::Applied { offset, fuzz };
+                break;
+            }
+        }
+        results.push(result);
+    }
+    (out, results)
+}
+
+/// fuzz 级别下首尾各能忽略多少行：只忽略上下文行，且至少保留一行上下文之外的内容
+fn context_trim(lines: &[HunkLine], fuzz: usize) -> (usize, usize) {
+    let is_ctx = |l: &HunkLine| matches!(l, HunkLine::Context(_));
+    let head = lines.iter().take_while(|l| is_ctx(l)).count().min(fuzz);
+    let tail = lines.iter().rev().take_while(|l| is_ctx(l)).count().min(fuzz);
+    if head + tail >= lines.len() { (0, 0) } else { (head, tail) }
+}
+
+/// 从期望位置开始向两侧交替搜索，找到离期望位置最近的匹配
+fn find_match(lines: &[String], old: &[&String], expected: isize, min_pos: usize) -> Option<usize> {
+    if old.len() > lines.len() || min_pos > lines.len() - old.len() {
+        return None;
+    }
+    let max_pos = lines.len() - old.len();
+    let matches_at = |pos: usize| lines[pos..pos + old.len()].iter().zip(old).all(|(a, b)| a == *b);
+    let expected = expected.clamp(min_pos as isize, max_pos as isize) as usize;
+
+    for dist in 0..=max_pos - min_pos {
+        let down = expected.checked_sub(dist).filter(|&p| p >= min_pos);
+        let up = Some(expected + dist).filter(|&p| dist > 0 && p <= max_pos);
+        if down.is_none() && up.is_none() && dist > 0 {
+            break;
+        }
+        if let Some(pos) = down.into_iter().chain(up).find(|&p| matches_at(p)) {
+            return Some(pos);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::strings;
+    use crate::diff::unified_diff;
+
+    #[test]
+    fn parse_reads_headers_and_hunks() {
+        let text = "diff --git a/x.txt b/x.txt\n--- a/x.txt\t2024-01-01\n+++ b/x.txt\n@@ -1,2 +1,2 @@\n a\n-b\n+B\n\\ No newline at end of file\n";
+        let files = parse_patch(text).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].old_path, "a/x.txt");
+        assert_eq!(files[0].default_strip(), 1);
+        assert_eq!(files[0].target(1), "x.txt");
+        assert_eq!(
+            files[0].hunks[0].lines,
+            vec![HunkLine::Context("a".into()), HunkLine::Remove("b".into()), HunkLine::Add("B".into())]
+        );
+
+        assert!(parse_patch("@@ -1 +1 @@\n-a\n+b\n").is_err());
+        assert!(parse_patch("--- a\n+++ b\n@@ -1,3 +1,3 @@\n a\n").is_err());
+    }
+
+    #[test]
+    fn generated_diff_round_trips() {
+        let a = strings(&["1", "2", "3", "4", "5", "6", "7", "8", "9"]);
+        let b = strings(&["0", "1", "2", "three", "4", "5", "6", "7", "9", "10"]);
+        let files = parse_patch(&unified_diff(&a, &b, "a", "b", 1)).unwrap();
+        let (out, results) = apply_hunks(&a, &files[0].hunks, 0);
+        assert_eq!(out, b);
+        assert!(results.iter().all(|r| matches!(r, HunkResult::Applied { offset: 0, fuzz: 0 })));
+    }
+
+    #[test]
+    fn offset_and_fuzz_matching() {
+        let a = strings(&["a", "b", "c", "d", "e"]);
+        let b = strings(&["a", "b", "C", "d", "e"]);
+        let hunks = parse_patch(&unified_diff(&a, &b, "a", "b", 1)).unwrap().remove(0).hunks;
+
+        // 文件开头多了两行：按偏移找到
+        let shifted = strings(&["x", "y", "a", "b", "c", "d", "e"]);
+        let (out, results) = apply_hunks(&shifted, &hunks, 0);
+        assert_eq!(out, strings(&["x", "y", "a", "b", "C", "d", "e"]));
+        assert_eq!(results, vec![HunkResult::Applied { offset: 2, fuzz: 0 }]);
+
+        // 上下文行变了：fuzz 0 拒绝，fuzz 1 可以应用
+        let drifted = strings(&["a", "B", "c", "d", "e"]);
+        assert_eq!(apply_hunks(&drifted, &hunks, 0).1, vec![HunkResult::Rejected]);
+        let (out, results) = apply_hunks(&drifted, &hunks, 1);
+        assert_eq!(out, strings(&["a", "B", "C", "d", "e"]));
+        assert_eq!(results, vec![HunkResult::Applied { offset: 0, fuzz: 1 }]);
+    }
+}
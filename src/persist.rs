@@ -1,28 +1,358 @@
 //! Memento持久化模块。
 //! 供Workspace调用。
+//! 另提供原子写文件（临时文件 + fsync + rename），供保存文件与快照共用。
 
-use std::{collections::HashMap, fs, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsString,
+    fmt,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use chrono::Local;
 use serde::{Serialize, Deserialize};
-use crate::error::AppResult;
+use serde_json::Value;
+use tempfile::Builder;
+use crate::error::{AppError, AppResult};
 
+/// 覆盖已有文件前是否保留备份
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackupMode {
+    #[default]
+    Off,
+    /// `file~`，每次覆盖
+    Tilde,
+    /// `file.YYYYmmdd-HHMMSS~`，每次保存新增一份
+    Timestamp,
+}
+
+/// 原子地把 data 写到 path：
+/// 先写同目录下的临时文件并 fsync，再 rename 覆盖目标，
+/// 中途崩溃或磁盘写满时原文件保持不变。已有文件的权限会被保留。
+pub fn atomic_write(path: &Path, data: &[u8], backup: BackupMode) -> AppResult<()> {
+    let fail = |reason: String| AppError::SaveFailed(format!("{}: {}", path.display(), reason));
+
+    // 目标是符号链接时写到链接指向的文件，而不是把链接本身替换掉
+    let target: PathBuf = match fs::symlink_metadata(path) {
+        Ok(m) if m.file_type().is_symlink() => {
+            fs::canonicalize(path).map_err(|e| fail(format!("cannot resolve symlink: {}", e)))?
+        }
+        _ => path.to_path_buf(),
+    };
+    let dir = match target.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let existing = fs::metadata(&target).ok();
+
+    if let Some(meta) = &existing {
+        if meta.is_dir() {
+            return Err(fail("is a directory".into()));
+        }
+        if backup != BackupMode::Off {
+            let bak = backup_path(&target, backup);
+            fs::copy(&target, &bak)
+                .map_err(|e| fail(format!("cannot write backup {}: {}", bak.display(), e)))?;
+        }
+    }
+
+    let mut tmp = temp_builder()
+        .tempfile_in(&dir)
+        .map_err(|e| fail(format!("cannot create temp file in {}: {}", dir.display(), e)))?;
+    tmp.write_all(data)
+        .and_then(|_| tmp.as_file().sync_all())
+        .map_err(|e| fail(format!("write failed: {}", e)))?;
+    if let Some(meta) = &existing {
+        fs::set_permissions(tmp.path(), meta.permissions())
+            .map_err(|e| fail(format!("cannot preserve permissions: {}", e)))?;
+    }
+    tmp.persist(&target)
+        .map_err(|e| fail(format!("rename failed: {}", e.error)))?;
+
+    // rename 本身也要落盘；部分平台不支持对目录 fsync，失败可以忽略
+    #[cfg(unix)]
+    if let Ok(d) = fs::File::open(&dir) {
+        let _ = d.sync_all();
+    }
+    Ok(())
+}
+
+/// 加载或保存时记下的磁盘状态，用于发现文件被其他程序修改
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiskStamp {
+    pub mtime: Option<SystemTime>,
+    pub size: u64,
+    pub hash: u64,
+}
+
+impl DiskStamp {
+    /// 读取文件当前状态；文件不存在时返回 None
+    pub fn of_file(path: &Path) -> io::Result<Option<Self>> {
+        let data = match fs::read(path) {
+            Ok(d) => d,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let meta = fs::metadata(path)?;
+        Ok(Some(Self {
+            mtime: meta.modified().ok(),
+            size: data.len() as u64,
+            hash: fnv1a(&data),
+        }))
+    }
+
+    /// 与记录时相比磁盘上的文件是否变了。
+    /// mtime 与大小都没变时直接认为没变；否则再比较内容哈希，只是 touch 过不算修改。
+    pub fn changed_since(recorded: Option<DiskStamp>, path: &Path) -> io::Result<bool> {
+        let meta = match fs::metadata(path) {
+            Ok(m) => m,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(recorded.is_some()),
+            Err(e) => return Err(e),
+        };
+        let Some(old) = recorded else { return Ok(true) };
+        if meta.modified().ok() == old.mtime && meta.len() == old.size {
+            return Ok(false);
+        }
+        Ok(fnv1a(&fs::read(path)?) != old.hash)
+    }
+}
+
+/// FNV-1a：结果稳定，不随编译器版本变化
+pub fn fnv1a(data: &[u8]) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in data {
+        h ^= b as u64;
+        h = h.wrapping_mul(0x0100_0000_01b3);
+    }
+    h
+}
+
+/// 临时文件默认只有属主可读写；新建文件时改用 0666（再经 umask），与直接创建文件一致
+fn temp_builder() -> Builder<'static, 'static> {
+    #[allow(unused_mut)]
+    let mut b = Builder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        b.permissions(fs::Permissions::from_mode(0o666));
+    }
+    b
+}
+
+fn backup_path(path: &Path, mode: BackupMode) -> PathBuf {
+    let mut name: OsString = path.file_name().unwrap_or_default().to_os_string();
+    if mode == BackupMode::Timestamp {
+        name.push(format!(".{}", Local::now().format("%Y%m%d-%H%M%S")));
+    }
+    name.push("~");
+    path.with_file_name(name)
+}
+
+/// 文件中的位置，行列都从 1 起
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position { pub line: usize, pub col: usize }
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
+}
+
+// 缺少的字段取默认值，以后给 FileFlags 加字段不会让旧文件读不出来
 #[derive(Serialize, Deserialize, Default, Debug)]
-pub struct FileFlags { pub modified: bool, pub logging: bool }
+#[serde(default)]
+pub struct FileFlags {
+    pub modified: bool,
+    pub logging: bool,
+    /// 最近一次 show 的行范围
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub view: Option<(usize, usize)>,
+    /// 当前位置标记
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub bookmarks: BTreeMap<String, Position>,
+    /// 以只读方式打开
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub readonly: bool,
+}
+
+/// 寄存器内容：字符级（来自 delete / replace）或整行（来自 yank / delete-lines）。
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Register {
+    Chars(String),
+    Lines(Vec<String>),
+}
+
+/// 当前的快照格式版本。格式有不兼容的变化时加一，并在 MIGRATIONS 末尾补一步迁移。
+pub const MEMENTO_VERSION: u32 = 2;
+
+/// 迁移链：第 i 项把版本 i+1 的 JSON 升级到版本 i+2
+const MIGRATIONS: &[fn(Value) -> AppResult<Value>] = &[migrate_v1_to_v2];
+
+/// v1：没有 version 字段的旧格式，寄存器可能缺失。补上版本号即可。
+fn migrate_v1_to_v2(mut value: Value) -> AppResult<Value> {
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| AppError::BadMemento("top level is not an object".into()))?;
+    obj.insert("version".into(), Value::from(2));
+    obj.entry("registers").or_insert_with(|| Value::Object(Default::default()));
+    Ok(value)
+}
 
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct WorkspaceMemento {
+    pub version: u32,
     pub open_files: HashMap<String, FileFlags>,
     pub active: Option<String>,
+    // 旧版快照没有这个字段，按空处理
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub registers: BTreeMap<char, Register>,
 }
 
 impl WorkspaceMemento {
     pub fn save(&self, path: &Path) -> AppResult<()> {
         let data = serde_json::to_string_pretty(self)?;
-        fs::write(path, data)?;
+        atomic_write(path, data.as_bytes(), BackupMode::Off)?;
         Ok(())
     }
 
-    pub fn load(path: &Path) -> AppResult<Self> {
+    /// 读取快照并迁移到当前版本，同时返回文件原本的版本号
+    pub fn load(path: &Path) -> AppResult<(Self, u32)> {
         let s = fs::read_to_string(path)?;
-        Ok(serde_json::from_str(&s)?)
+        Self::from_json(&s)
     }
-}
\ No newline at end of file
+
+    pub fn from_json(s: &str) -> AppResult<(Self, u32)> {
+        let mut value: Value = serde_json::from_str(s)
+            .map_err(|e| AppError::BadMemento(format!("not valid JSON: {}", e)))?;
+        let version = match value.get("version") {
+            None => 1,
+            Some(v) => v
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .filter(|&v| v >= 1)
+                .ok_or_else(|| AppError::BadMemento(format!("invalid version field: {}", v)))?,
+        };
+        if version > MEMENTO_VERSION {
+            return Err(AppError::BadMemento(format!(
+                "format version {} is newer than supported version {}",
+                version, MEMENTO_VERSION
+            )));
+        }
+
+        for step in &MIGRATIONS[version as usize - 1..] {
+            value = step(value)?;
+        }
+        let m = serde_json::from_value(value)
+            .map_err(|e| AppError::BadMemento(format!("unexpected structure: {}", e)))?;
+        Ok((m, version))
+    }
+}
+
+/// 启动时恢复工作区的结果，供 workspace-info 查看
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    pub source: Option<PathBuf>,
+    /// 快照文件原本的格式版本
+    pub from_version: Option<u32>,
+    pub files: usize,
+    pub warnings: Vec<String>,
+}
+
+impl RestoreReport {
+    pub fn describe(&self) -> String {
+        let mut out = match (&self.source, self.from_version) {
+            (Some(src), Some(v)) if v == MEMENTO_VERSION => {
+                format!("restored from {} (format v{})", src.display(), v)
+            }
+            (Some(src), Some(v)) => format!(
+                "restored from {} (format v{}, migrated to v{})",
+                src.display(),
+                v,
+                MEMENTO_VERSION
+            ),
+            (Some(src), None) => format!("could not restore from {}", src.display()),
+            (None, _) => "no saved workspace found; started empty".to_string(),
+        };
+        if self.from_version.is_some() {
+            out.push_str(&format!("\nfiles restored: {}", self.files));
+        }
+        for w in &self.warnings {
+            out.push_str(&format!("\n[warn] {}", w));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn atomic_write_replaces_content_and_keeps_backup() {
+        let tmp = tempdir().unwrap();
+        let path = tmp.path().join("a.txt");
+
+        // 新文件：不生成备份
+        atomic_write(&path, b"one", BackupMode::Tilde).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one");
+        assert!(!tmp.path().join("a.txt~").exists());
+
+        atomic_write(&path, b"two", BackupMode::Tilde).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two");
+        assert_eq!(fs::read_to_string(tmp.path().join("a.txt~")).unwrap(), "one");
+
+        // 目录里只剩目标文件与备份，没有残留的临时文件
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn atomic_write_preserves_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempdir().unwrap();
+        let path = tmp.path().join("script.sh");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o750)).unwrap();
+
+        atomic_write(&path, b"new", BackupMode::Off).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o750);
+    }
+
+    #[test]
+    fn legacy_memento_is_migrated() {
+        let legacy = r#"{"open_files":{"a.txt":{"modified":true}},"active":"a.txt"}"#;
+        let (m, from) = WorkspaceMemento::from_json(legacy).unwrap();
+        assert_eq!(from, 1);
+        assert_eq!(m.version, MEMENTO_VERSION);
+        assert!(m.open_files["a.txt"].modified);
+        assert!(!m.open_files["a.txt"].logging);
+        assert!(m.registers.is_empty());
+
+        let current = serde_json::to_string(&m).unwrap();
+        assert_eq!(WorkspaceMemento::from_json(&current).unwrap().1, MEMENTO_VERSION);
+    }
+
+    #[test]
+    fn corrupt_or_future_memento_is_rejected() {
+        for bad in ["{not json", "[1, 2]", r#"{"version": 99, "open_files": {}}"#, r#"{"version": "x"}"#] {
+            let err = WorkspaceMemento::from_json(bad).unwrap_err();
+            assert_eq!(err.code(), 2006, "{}: {}", bad, err);
+        }
+    }
+
+    #[test]
+    fn atomic_write_reports_missing_directory() {
+        let tmp = tempdir().unwrap();
+        let path = tmp.path().join("no/such/dir/a.txt");
+
+        let err = atomic_write(&path, b"x", BackupMode::Off).unwrap_err();
+        assert!(matches!(err, AppError::SaveFailed(_)), "unexpected error: {}", err);
+    }
+}
@@ -18,6 +18,17 @@ pub struct Router {
     table: HashMap<&'static str, Handler>,
 }
 
+/// 命令链中一条子命令的执行条件（由它前面的分隔符决定）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chain {
+    /// 行首或 `;`：无条件执行
+    Always,
+    /// `&&`：上一条成功才执行
+    IfOk,
+    /// `||`：上一条失败才执行
+    IfErr,
+}
+
 impl Router {
     pub fn new() -> Self {
         let mut router = Router {
@@ -54,4 +65,119 @@ impl Router {
 
         Ok((*handler, args))
     }
+
+    /// 按 `;`、`&&`、`||` 把一行拆成多条子命令，引号内和转义后的分隔符原样保留。
+    /// 子命令本身不在这里解析，交给 `resolve` 处理。
+    pub fn split_chain(line: &str) -> AppResult<Vec<(Chain, String)>> {
+        let mut out = Vec::new();
+        let mut cur = String::new();
+        let mut cond = Chain::Always;
+        let mut quote: Option<char> = None;
+        let mut chars = line.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match quote {
+                Some(q) => {
+                    cur.push(c);
+                    if c == q {
+                        quote = None;
+                    } else if c == '\\' && q == '"' {
+                        // 双引号内的转义字符：连同下一个字符一起保留
+                        if let Some(n) = chars.next() { cur.push(n); }
+                    }
+                }
+                None => match c {
+                    '\'' | '"' => {
+                        quote = Some(c);
+                        cur.push(c);
+                    }
+                    '\\' => {
+                        cur.push(c);
+                        if let Some(n) = chars.next() { cur.push(n); }
+                    }
+                    ';' => {
+                        Self::push_segment(&mut out, cond, &mut cur, true)?;
+                        cond = Chain::Always;
+                    }
+                    '&' | '|' if chars.peek() == Some(&c) => {
+                        chars.next();
+                        Self::push_segment(&mut out, cond, &mut cur, false)?;
+                        cond = if c == '&' { Chain::IfOk } else { Chain::IfErr };
+                    }
+                    _ => cur.push(c),
+                },
+            }
+        }
+
+        if quote.is_some() {
+            return Err(AppError::InvalidCommand("missing closing quote".into()));
+        }
+        // 行尾的 `&&` / `||` 后面必须还有命令
+        if cond != Chain::Always && cur.trim().is_empty() {
+            return Err(AppError::InvalidCommand("missing command after && / ||".into()));
+        }
+        Self::push_segment(&mut out, cond, &mut cur, true)?;
+        Ok(out)
+    }
+
+    fn push_segment(
+        out: &mut Vec<(Chain, String)>,
+        cond: Chain,
+        cur: &mut String,
+        allow_empty: bool,
+    ) -> AppResult<()> {
+        let seg = cur.trim().to_string();
+        cur.clear();
+        if seg.is_empty() {
+            // `a ;; b` 或行尾的 `;` 可以容忍，`&&` / `||` 两侧不能为空
+            if allow_empty && cond == Chain::Always {
+                return Ok(());
+            }
+            return Err(AppError::InvalidCommand("empty command in chain".into()));
+        }
+        out.push((cond, seg));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segs(line: &str) -> Vec<(Chain, String)> {
+        Router::split_chain(line).expect("split_chain failed")
+    }
+
+    #[test]
+    fn split_chain_handles_all_separators() {
+        let v = segs("load a.txt; append x && show || undo");
+        assert_eq!(
+            v,
+            vec![
+                (Chain::Always, "load a.txt".to_string()),
+                (Chain::Always, "append x".to_string()),
+                (Chain::IfOk, "show".to_string()),
+                (Chain::IfErr, "undo".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_chain_keeps_quoted_separators() {
+        let v = segs(r#"append "a; b && c" ; insert 1:1 'x || y' ; append a\;b"#);
+        assert_eq!(v.len(), 3);
+        assert_eq!(v[0].1, r#"append "a; b && c""#);
+        assert_eq!(v[1].1, "insert 1:1 'x || y'");
+        assert_eq!(v[2].1, r"append a\;b");
+    }
+
+    #[test]
+    fn split_chain_rejects_dangling_conditionals() {
+        assert!(Router::split_chain("show &&").is_err());
+        assert!(Router::split_chain("|| show").is_err());
+        assert!(Router::split_chain("show && && undo").is_err());
+        assert!(Router::split_chain("append \"unterminated").is_err());
+        // 多余的 `;` 不算错误
+        assert_eq!(segs("show;; undo;").len(), 2);
+    }
 }
@@ -0,0 +1,105 @@
+//! 路径沙箱：开启后所有文件操作只能落在工作区根目录及额外允许的目录内。
+//! 判断前先把路径解析成真实路径（展开 `..` 和符号链接），
+//! 防止通过 `../` 或指向外部的符号链接绕过。
+
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+use crate::error::{AppError, AppResult};
+
+#[derive(Clone, Debug)]
+pub struct Sandbox {
+    /// 已规范化的允许目录，第一个是工作区根目录
+    roots: Vec<PathBuf>,
+}
+
+impl Sandbox {
+    /// 允许的目录必须存在
+    pub fn new(root: &Path, extra: &[PathBuf]) -> AppResult<Self> {
+        let mut roots = Vec::with_capacity(extra.len() + 1);
+        for dir in std::iter::once(root).chain(extra.iter().map(|p| p.as_path())) {
+            let real = fs::canonicalize(dir).map_err(|e| {
+                AppError::InvalidArgs(format!("sandbox root {}: {}", dir.display(), e))
+            })?;
+            roots.push(real);
+        }
+        Ok(Self { roots })
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// 检查路径是否落在允许的目录内，返回解析后的真实路径
+    pub fn check(&self, path: &Path) -> AppResult<PathBuf> {
+        let real = real_path(path)?;
+        if self.roots.iter().any(|root| real.starts_with(root)) {
+            Ok(real)
+        } else {
+            Err(AppError::OutsideSandbox(format!("{} (resolves to {})", path.display(), real.display())))
+        }
+    }
+}
+
+/// 解析真实路径；与 `fs::canonicalize` 不同，末尾不存在的部分（例如即将新建的文件）也可以解析。
+/// 逐段处理：存在的部分交给系统展开符号链接，不存在的部分不可能是链接，按字面处理 `..`。
+pub fn real_path(path: &Path) -> io::Result<PathBuf> {
+    let mut cur = PathBuf::new();
+    let mut exists = true;
+    for comp in std::path::absolute(path)?.components() {
+        match comp {
+            Component::Prefix(_) | Component::RootDir => cur.push(comp),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                cur.pop();
+                // 退回到已存在的目录后，后面的部分又需要交给系统展开
+                exists = fs::symlink_metadata(&cur).is_ok();
+            }
+            Component::Normal(name) => {
+                cur.push(name);
+                if exists {
+                    match fs::canonicalize(&cur) {
+                        Ok(real) => cur = real,
+                        Err(e) if e.kind() == io::ErrorKind::NotFound => exists = false,
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+        }
+    }
+    Ok(cur)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_parent_escapes_and_outside_symlinks() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("root");
+        let outside = tmp.path().join("outside");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        let sandbox = Sandbox::new(&root, &[]).unwrap();
+
+        assert!(sandbox.check(&root.join("sub/new.txt")).is_ok());
+        assert!(sandbox.check(&root.join("missing/dir/../x.txt")).is_ok());
+        assert!(sandbox.check(&root.join("missing/../../outside")).is_err());
+        let err = sandbox.check(&root.join("sub/../../outside/x.txt")).unwrap_err();
+        assert_eq!(err.code(), 2007);
+        assert!(sandbox.check(Path::new("/etc/passwd")).is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+            assert!(sandbox.check(&root.join("link/x.txt")).is_err());
+            assert!(sandbox.check(&root.join("missing/../link/x.txt")).is_err());
+
+            // 额外允许的目录
+            let wider = Sandbox::new(&root, std::slice::from_ref(&outside)).unwrap();
+            assert!(wider.check(&root.join("link/x.txt")).is_ok());
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! 交换文件：未保存的修改定期写到 base_dir 下，进程异常退出后可以恢复。
+//! 文件名形如 `.a.txt-1f2e3d4c.swp`，哈希部分区分不同目录下的同名文件，
+//! 真正的源文件路径记录在交换文件内容里。
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use chrono::Local;
+use serde::{Serialize, Deserialize};
+use crate::{
+    error::AppResult,
+    persist::{atomic_write, fnv1a, BackupMode},
+};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SwapFile {
+    pub path: String,
+    pub lines: Vec<String>,
+    pub final_newline: bool,
+    pub saved_at: String,
+}
+
+impl SwapFile {
+    pub fn new(path: &Path, lines: Vec<String>, final_newline: bool) -> Self {
+        Self {
+            path: path.to_string_lossy().into_owned(),
+            lines,
+            final_newline,
+            saved_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+
+    /// 源文件对应的交换文件路径
+    pub fn path_for(base_dir: &Path, file: &Path) -> PathBuf {
+        let name = file.file_name().unwrap_or_default().to_string_lossy();
+        let hash = fnv1a(file.to_string_lossy().as_bytes());
+        base_dir.join(format!(".{}-{:08x}.swp", name, hash as u32))
+    }
+
+    pub fn write(&self, swap_path: &Path) -> AppResult<()> {
+        let data = serde_json::to_string(self)?;
+        atomic_write(swap_path, data.as_bytes(), BackupMode::Off)
+    }
+
+    pub fn read(swap_path: &Path) -> AppResult<Self> {
+        let s = fs::read_to_string(swap_path)?;
+        Ok(serde_json::from_str(&s)?)
+    }
+
+    /// 列出 base_dir 下所有交换文件，解析失败的也一并返回，由调用方决定如何提示
+    pub fn scan(base_dir: &Path) -> Vec<(PathBuf, AppResult<SwapFile>)> {
+        let Ok(entries) = fs::read_dir(base_dir) else { return Vec::new() };
+        let mut found: Vec<_> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "swp"))
+            .map(|p| {
+                let swap = SwapFile::read(&p);
+                (p, swap)
+            })
+            .collect();
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+        found
+    }
+}
@@ -0,0 +1,19 @@
+//! 测试共用的小工具，只在 cfg(test) 下编译。
+
+use crate::{
+    application::Application, event::EventBus, persist::RestoreReport, router::Router, workspace::Workspace,
+};
+
+pub fn strings(v: &[&str]) -> Vec<String> {
+    v.iter().map(|s| s.to_string()).collect()
+}
+
+/// 用给定的 Workspace 构造 Application：默认 Router，EventBus 上不挂订阅者
+pub fn app_with(workspace: Workspace) -> Application {
+    Application {
+        router: Router::new(),
+        workspace,
+        bus: EventBus::new(),
+        restore: RestoreReport::default(),
+    }
+}
@@ -0,0 +1,176 @@
+//! 回收站：rm 不直接删除，而是把文件或目录移到 `base_dir/.trash/<id>/` 下，
+//! 同目录的 `origin` 文件记下原来的路径，之后可以原样放回。
+//! id 是删除时的时间戳，按字典序排列就是时间顺序。
+//! 另外提供 mv / cp 用的移动与递归复制，跨文件系统时移动退化为复制后删除。
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use chrono::Local;
+use crate::error::{AppError, AppResult};
+
+pub const TRASH_DIR: &str = ".trash";
+const ORIGIN_FILE: &str = "origin";
+
+#[derive(Debug, Clone)]
+pub struct TrashEntry {
+    pub id: String,
+    pub origin: PathBuf,
+    pub deleted_at: SystemTime,
+}
+
+impl TrashEntry {
+    /// 回收站里保存的那份文件或目录
+    fn item(&self, base_dir: &Path) -> PathBuf {
+        base_dir
+            .join(TRASH_DIR)
+            .join(&self.id)
+            .join(self.origin.file_name().unwrap_or_default())
+    }
+}
+
+/// 把文件或目录移进回收站，返回条目 id
+pub fn put(base_dir: &Path, path: &Path) -> AppResult<String> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| AppError::InvalidArgs(format!("can't remove {}", path.display())))?;
+    let trash = base_dir.join(TRASH_DIR);
+    let stamp = Local::now().format("%Y%m%d-%H%M%S-%3f").to_string();
+    let mut id = stamp.clone();
+    let mut n = 1;
+    while trash.join(&id).exists() {
+        id = format!("{}-{}", stamp, n);
+        n += 1;
+    }
+
+    let dir = trash.join(&id);
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(ORIGIN_FILE), std::path::absolute(path)?.to_string_lossy().as_bytes())?;
+    if let Err(e) = move_path(path, &dir.join(name)) {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(e.into());
+    }
+    Ok(id)
+}
+
+/// 列出回收站的全部条目，最新的在前；缺少 origin 的目录忽略
+pub fn list(base_dir: &Path) -> AppResult<Vec<TrashEntry>> {
+    let Ok(entries) = fs::read_dir(base_dir.join(TRASH_DIR)) else { return Ok(Vec::new()) };
+    let mut found = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let Ok(origin) = fs::read_to_string(entry.path().join(ORIGIN_FILE)) else { continue };
+        found.push(TrashEntry {
+            id: entry.file_name().to_string_lossy().into_owned(),
+            origin: PathBuf::from(origin),
+            deleted_at: entry.metadata()?.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        });
+    }
+    found.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(found)
+}
+
+pub fn find(base_dir: &Path, id: &str) -> AppResult<TrashEntry> {
+    list(base_dir)?
+        .into_iter()
+        .find(|e| e.id == id)
+        .ok_or_else(|| AppError::InvalidArgs(format!("no trash entry {}", id)))
+}
+
+/// 把条目放回原处；原路径上已有东西时拒绝
+pub fn restore(base_dir: &Path, entry: &TrashEntry) -> AppResult<()> {
+    if fs::symlink_metadata(&entry.origin).is_ok() {
+        return Err(AppError::InvalidArgs(format!("{} already exists", entry.origin.display())));
+    }
+    if let Some(parent) = entry.origin.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    move_path(&entry.item(base_dir), &entry.origin)?;
+    fs::remove_dir_all(base_dir.join(TRASH_DIR).join(&entry.id))?;
+    Ok(())
+}
+
+/// 移动文件或目录；跨文件系统时复制后删除原件
+pub fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            copy_tree(from, to)?;
+            remove_all(from)
+        }
+        other => other,
+    }
+}
+
+/// 递归复制；符号链接按链接本身复制，不跟进去
+pub fn copy_tree(from: &Path, to: &Path) -> io::Result<()> {
+    let meta = fs::symlink_metadata(from)?;
+    if meta.is_symlink() {
+        copy_symlink(from, to)
+    } else if meta.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_tree(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(to).is_ok() {
+        fs::remove_file(to)?;
+    }
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    fs::copy(from, to).map(|_| ())
+}
+
+fn remove_all(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removed_files_and_dirs_can_be_restored() {
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path();
+        let file = base.join("a.txt");
+        let dir = base.join("sub");
+        fs::write(&file, "a").unwrap();
+        fs::create_dir_all(dir.join("deep")).unwrap();
+        fs::write(dir.join("deep/b.txt"), "b").unwrap();
+
+        let file_id = put(base, &file).unwrap();
+        put(base, &dir).unwrap();
+        assert!(!file.exists() && !dir.exists());
+        let entries = list(base).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].origin, dir);
+
+        restore(base, &entries[0]).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("deep/b.txt")).unwrap(), "b");
+
+        // 原处已有同名文件时不覆盖
+        fs::write(&file, "new").unwrap();
+        let entry = find(base, &file_id).unwrap();
+        assert!(restore(base, &entry).is_err());
+        fs::remove_file(&file).unwrap();
+        restore(base, &entry).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "a");
+        assert!(list(base).unwrap().is_empty());
+    }
+}
@@ -2,16 +2,18 @@
 //! 管理多文件上下文、状态持久化。
 
 use std::{
-    collections::HashMap, 
+    collections::{BTreeMap, HashMap}, 
+    ffi::OsStr,
     fs, 
     io,
     path::{Path, PathBuf},
     fmt::Write,
+    time::SystemTime,
 };
 
 
 use crate::{
-    commands::doc_command::DocCommand, editor::Editor, error::{AppError, AppResult}, persist::{FileFlags, WorkspaceMemento}
+    commands::doc_command::{DocCommand, ReplaceContentCommand}, editor::{Editor, IndentConfig}, hooks::SaveHooks, history::{self, Retention, Snapshot}, logging, sandbox::Sandbox, swap::SwapFile, trash::{self, TrashEntry, TRASH_DIR}, error::{AppError, AppResult}, persist::{BackupMode, DiskStamp, FileFlags, Position, Register, WorkspaceMemento, MEMENTO_VERSION}
 };
 
 
@@ -20,48 +22,128 @@ pub struct Workspace {
     editors: HashMap<PathBuf, Editor>,
     active: Option<PathBuf>,
     base_dir: PathBuf,
+    // 寄存器在所有 Editor 之间共享
+    registers: BTreeMap<char, Register>,
+    // 保存钩子的工作区默认值
+    save_hooks: SaveHooks,
+    // 覆盖文件前的备份方式
+    backup: BackupMode,
+    // 开启时限制文件操作的范围
+    sandbox: Option<Sandbox>,
+    // 本地历史快照的保留策略
+    history: Retention,
+    // 各文件最近一次写入交换文件时的版本号
+    swapped: HashMap<PathBuf, u64>,
+    // 启动时发现、尚未处理的交换文件：源文件路径 -> 交换文件路径
+    pending_swaps: BTreeMap<PathBuf, PathBuf>,
+    // 查看模式：之后打开的文件一律只读，也不能新建
+    view_only: bool,
+    // 打开文件的大小上限，None 表示不限
+    max_file_size: Option<u64>,
 }
 
+/// 无名寄存器：yank / put 省略寄存器名时使用
+pub const UNNAMED_REGISTER: char = '"';
+/// 最近一次删除的内容
+pub const DELETED_REGISTER: char = '-';
+/// 会话快照所在的子目录
+pub const SESSIONS_DIR: &str = ".sessions";
+
+/// 工作区根目录的标记：工作区快照文件本身，类似 git 的 `.git`
+pub const WORKSPACE_MARKER: &str = ".editor_workspace";
+/// 用于指定工作区根目录的环境变量
+pub const WORKSPACE_ENV: &str = "TEXTEDITOR_WORKSPACE";
+/// 既没有指定、也没有找到标记时使用的目录（相对于当前目录）
+const DEFAULT_BASE_DIR: &str = "work_dir";
+/// 默认的文件大小上限
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
 impl Workspace {
-    // 默认生成逻辑
-    pub fn default() -> Self {
-        let base = PathBuf::from("work_dir");
-        let base_d = if !base.exists() {
+    /// 确定工作区根目录，优先级：命令行 `--workspace` > 环境变量 >
+    /// 从 cwd 逐级向上第一个含标记文件的目录 > 默认的 `work_dir`
+    pub fn discover_root(explicit: Option<&Path>, env: Option<&OsStr>, cwd: &Path) -> PathBuf {
+        if let Some(dir) = explicit {
+            return dir.to_path_buf();
+        }
+        if let Some(dir) = env.filter(|v| !v.is_empty()) {
+            return PathBuf::from(dir);
+        }
+        cwd.ancestors()
+            .find(|dir| dir.join(WORKSPACE_MARKER).is_file())
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_BASE_DIR))
+    }
+
+    /// 工作区快照的位置；启动时读取与退出时写入都用这一个路径
+    pub fn memento_path(&self) -> PathBuf {
+        self.base_dir.join(WORKSPACE_MARKER)
+    }
+
+    /// 以指定目录为 base_dir 创建工作区，目录不存在时自动创建
+    pub fn new(base: PathBuf) -> Self {
+        if !base.exists() {
             fs::create_dir_all(&base).ok();
-            base
-        } else {
-            base
-        };
+        }
         Self {
             editors: HashMap::new(),
             active: None,
-            base_dir: base_d,
+            base_dir: base,
+            registers: BTreeMap::new(),
+            save_hooks: SaveHooks::default(),
+            backup: BackupMode::default(),
+            history: Retention::default(),
+            sandbox: None,
+            swapped: HashMap::new(),
+            pending_swaps: BTreeMap::new(),
+            view_only: false,
+            max_file_size: Some(DEFAULT_MAX_FILE_SIZE),
         }
     }
 
     /// 用于处理需要undo的函数。
     pub fn exec_doc(&mut self, cmd: Box<dyn DocCommand>) -> AppResult<()> {
-        let ed = self.get_active_editor_mut()?;
+        let ed = self.get_writable_editor_mut()?;
+        ed.exec_doc(cmd)
+    }
+
+    /// 对指定文件执行命令，不切换活跃文件
+    pub fn exec_doc_at(&mut self, path: impl AsRef<Path>, cmd: Box<dyn DocCommand>) -> AppResult<()> {
+        let key = path.as_ref();
+        let ed = self
+            .editors
+            .get_mut(key)
+            .ok_or_else(|| AppError::InvalidArgs(format!("file not open: {}", key.display())))?;
+        Self::ensure_writable(key, ed)?;
         ed.exec_doc(cmd)
     }
 
     pub fn undo(&mut self) -> AppResult<()> {
-        let ed = self.get_active_editor_mut()?;
+        let ed = self.get_writable_editor_mut()?;
         ed.undo()
     }
 
     pub fn redo(&mut self) -> AppResult<()> {
-        let ed = self.get_active_editor_mut()?;
+        let ed = self.get_writable_editor_mut()?;
         ed.redo()
     }
 
     // 以下为不需要undo的函数。
 
+    pub fn set_indent_config(&mut self, indent: IndentConfig) -> AppResult<()> {
+        let ed = self.get_active_editor_mut()?;
+        ed.set_indent_config(indent);
+        Ok(())
+    }
+
     //  文件处理函数
     /// 初始化文件，如果文件已存在，直接返回错误。
     pub fn init(&mut self, i_path: impl AsRef<Path>, i_logging: bool) -> AppResult<()> {
         let path: &Path = i_path.as_ref();
         let key: PathBuf = path.to_path_buf();
+        self.check_path(path)?;
+        if self.view_only {
+            return Err(AppError::ReadOnly("view mode, can't create files".into()));
+        }
 
         if self.editors.contains_key(&key) {
             return Err(AppError::InvalidArgs("file already exists!".into()));
@@ -79,29 +161,61 @@ impl Workspace {
     }
 
     // 用AsRef<Path>，调用方可传入多种类型。
-    /// 加载文件。
+    /// 加载文件。查看模式下或没有写权限的文件以只读方式打开。
     pub fn load(&mut self, i_path: impl AsRef<Path>) -> AppResult<()> {
+        self.load_with(i_path, false)
+    }
+
+    /// 加载文件；二进制文件和超过大小上限的文件默认拒绝，force 时照样打开。
+    /// 不是合法 UTF-8 的文件强制打开时按有损方式转换，保存会破坏原内容，因此以只读方式打开。
+    pub fn load_with(&mut self, i_path: impl AsRef<Path>, force: bool) -> AppResult<()> {
         let path: &Path = i_path.as_ref();
         let key: PathBuf = path.to_path_buf();
+        self.check_path(path)?;
 
-        let content = match fs::read_to_string(path) {
-            Ok(s) => s,
-            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
-            Err(e) => return Err(AppError::Io(e)),
-        };
+        let (content, lossy) = read_text(path, if force { None } else { self.max_file_size }, force)?;
 
         // 如果已存在，直接读取；否则新建一个editor
         let ed = self
             .editors
             .entry(key.clone())
-            // or_insert_with():需要一个显式闭包或者函数作传入值。
-            .or_insert_with(Editor::new);
+            // or_default(): 不存在时用 Editor::default() 新建。
+            .or_default();
 
         ed.load_from(&content);
+        ed.set_disk_stamp(DiskStamp::of_file(path)?);
+        ed.set_locked(lossy || self.view_only || !is_writable(path));
         self.active = Some(key);
         Ok(())
     }
 
+    /// 设置已打开文件由用户指定的只读标记；查看模式、没有写权限等情况下取消后仍然只读
+    pub fn set_readonly(&mut self, path: impl AsRef<Path>, readonly: bool) -> AppResult<()> {
+        let key = path.as_ref();
+        let ed = self
+            .editors
+            .get_mut(key)
+            .ok_or_else(|| AppError::InvalidArgs(format!("file not open: {}", key.display())))?;
+        ed.set_readonly(readonly);
+        Ok(())
+    }
+
+    pub fn max_file_size(&self) -> Option<u64> {
+        self.max_file_size
+    }
+
+    pub fn set_max_file_size(&mut self, limit: Option<u64>) {
+        self.max_file_size = limit;
+    }
+
+    pub fn view_only(&self) -> bool {
+        self.view_only
+    }
+
+    pub fn set_view_only(&mut self, view_only: bool) {
+        self.view_only = view_only;
+    }
+
     pub fn edit(&mut self, i_path: impl AsRef<Path>) -> AppResult<()> {
         let path: &Path = i_path.as_ref();
         let key: PathBuf = path.to_path_buf();
@@ -116,6 +230,8 @@ impl Workspace {
     pub fn close(&mut self) -> AppResult<()>{
         if let Some(active_path) = self.active.take() {
             self.editors.remove(&active_path);
+            // 关闭即放弃未保存的修改，交换文件随之删除
+            self.remove_swap(&active_path);
         } else {
             return Err(AppError::InternalError("no file to be closed".into()));
         }
@@ -150,32 +266,639 @@ impl Workspace {
         for (path, editor) in &self.editors {
             let is_active: bool = self.is_active_equal_to(path);
             let modified: bool = editor.is_modified();
-            let line = Self::write_editor(path, is_active, modified);
+            let mut line = Self::write_editor(path, is_active, modified);
+            if editor.is_readonly() {
+                line.push_str(" [readonly]");
+            }
             let _ = writeln!(&mut editor_list, "{}", line);
         }
         Ok(editor_list)
     }
 
-    pub fn save_file(&mut self, path: impl AsRef<Path>) -> AppResult<()> {
+    /// force 为 false 时，若磁盘上的文件在加载后被其他程序改过则拒绝保存
+    pub fn save_file(&mut self, path: impl AsRef<Path>, force: bool) -> AppResult<()> {
         let p = path.as_ref();
         let key: PathBuf = p.to_path_buf();
+        self.check_path(p)?;
 
         let ed = self
             .editors
             .get_mut(&key)
             .ok_or_else(|| AppError::InvalidArgs("no such path".into()))?;
 
-        ed.save_to(p)?;
+        Self::ensure_writable(p, ed)?;
+        if !force {
+            Self::check_disk(p, ed)?;
+        }
+        Self::run_save_hooks(ed, self.save_hooks)?;
+        ed.save_to(p, self.backup)?;
+        self.snapshot(p);
+        Ok(())
+    }
+
+    /// 先检查全部文件，有一个被外部修改就一个都不写。
+    /// 没有修改的只读文件跳过；只读文件带着修改（例如来自旧快照）时拒绝保存。
+    pub fn save_all(&mut self, force: bool) -> AppResult<()> {
+        for (p, ed) in &self.editors {
+            self.check_path(p)?;
+            if ed.is_modified() {
+                Self::ensure_writable(p, ed)?;
+            }
+        }
+        if !force {
+            for (p, ed) in self.editors.iter().filter(|(_, ed)| !ed.is_readonly()) {
+                Self::check_disk(p, ed)?;
+            }
+        }
+        for (p, ed) in self.editors.iter_mut().filter(|(_, ed)| !ed.is_readonly()) {
+            Self::run_save_hooks(ed, self.save_hooks)?;
+            ed.save_to(p, self.backup)?;
+        }
+        let paths: Vec<PathBuf> = self
+            .editors
+            .iter()
+            .filter(|(_, ed)| !ed.is_readonly())
+            .map(|(p, _)| p.clone())
+            .collect();
+        for p in paths {
+            self.snapshot(&p);
+        }
+        Ok(())
+    }
+
+    /// 保存后把磁盘内容记入本地历史，并按保留策略清理。
+    /// 此时文件已经写好，记录历史失败只给出警告，不把保存算作失败
+    fn snapshot(&self, path: &Path) {
+        let result = fs::read(path)
+            .map_err(AppError::from)
+            .and_then(|data| history::record(&self.base_dir, path, &data))
+            .and_then(|_| history::prune(&self.base_dir, path, self.history));
+        if let Err(e) = result {
+            eprintln!("[warn] failed to record history for {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn history_retention(&self) -> Retention {
+        self.history
+    }
+
+    pub fn set_history_retention(&mut self, retention: Retention) {
+        self.history = retention;
+    }
+
+    pub fn history_list(&self, path: impl AsRef<Path>) -> AppResult<Vec<Snapshot>> {
+        history::list(&self.base_dir, path.as_ref())
+    }
+
+    /// 读取历史快照的内容，按 Editor 的规则拆成行
+    pub fn history_lines(&self, path: impl AsRef<Path>, id: &str) -> AppResult<(Vec<String>, bool)> {
+        let snap = history::find(&self.base_dir, path.as_ref(), id)?;
+        let data = history::read_snapshot(&snap.path)?;
+        Ok(split_content(&String::from_utf8_lossy(&data)))
+    }
+
+    /// 用历史快照替换缓冲区内容，可以 undo
+    pub fn restore_history(&mut self, path: impl AsRef<Path>, id: &str) -> AppResult<()> {
+        let (lines, final_newline) = self.history_lines(&path, id)?;
+        self.exec_doc_at(path, Box::new(ReplaceContentCommand::new(lines, final_newline)))
+    }
+
+    /// 读取磁盘上的当前内容，按 Editor 的规则拆成行；文件不存在时为空。
+    /// 与 load 一样拒绝二进制文件和超过大小上限的文件。
+    pub fn disk_lines(&self, path: impl AsRef<Path>) -> AppResult<(Vec<String>, bool)> {
+        self.check_path(path.as_ref())?;
+        let (content, _) = read_text(path.as_ref(), self.max_file_size, false)?;
+        Ok(split_content(&content))
+    }
+
+    /// 用磁盘内容替换缓冲区。替换作为一条命令进入撤销栈，可以 undo 回重新加载前的内容。
+    /// 缓冲区有未保存修改、或磁盘上是二进制 / 超过大小上限的文件时需要 force；
+    /// 有损转换过的内容与 load 一样以只读方式打开。
+    pub fn reload(&mut self, path: impl AsRef<Path>, force: bool) -> AppResult<()> {
+        let key = path.as_ref();
+        self.check_path(key)?;
+        let (content, lossy) = read_text(key, if force { None } else { self.max_file_size }, force)?;
+        let (lines, final_newline) = split_content(&content);
+        let ed = self
+            .editors
+            .get_mut(key)
+            .ok_or_else(|| AppError::InvalidArgs(format!("file not open: {}", key.display())))?;
+        if ed.is_modified() && !force {
+            return Err(AppError::InvalidArgs(format!(
+                "{} has unsaved changes (use reload --force to discard them)",
+                key.display()
+            )));
+        }
+
+        if lines != ed.all_lines() || final_newline != ed.has_final_newline() {
+            ed.exec_doc(Box::new(ReplaceContentCommand::new(lines, final_newline)))?;
+        }
+        ed.set_modified(false);
+        ed.set_disk_stamp(DiskStamp::of_file(key)?);
+        ed.set_locked(lossy || self.view_only || !is_writable(key));
+        Ok(())
+    }
+
+    //  另存、改名与复制（作用于活跃文件）
+    /// 把活跃文件的内容写到新路径，之后这个 Editor 就对应新文件；原文件不动
+    pub fn save_as(&mut self, path: impl AsRef<Path>, force: bool) -> AppResult<()> {
+        let (from, to) = (self.active_path()?, path.as_ref().to_path_buf());
+        self.check_target(&from, &to, force)?;
+
+        let (hooks, backup) = (self.save_hooks, self.backup);
+        let ed = self.get_active_editor_mut()?;
+        Self::run_save_hooks(ed, hooks)?;
+        ed.save_to(&to, backup)?;
+        // 原文件只读不影响新文件
+        ed.set_readonly(false);
+        ed.set_locked(false);
+        self.rekey(&from, &to);
+        self.snapshot(&to);
+        Ok(())
+    }
+
+    /// 把活跃文件改名：磁盘上的文件（存在时）、Editor、日志和本地历史一起移到新路径。
+    /// 未保存的修改保留在缓冲区里。
+    pub fn rename(&mut self, path: impl AsRef<Path>, force: bool) -> AppResult<()> {
+        let (from, to) = (self.active_path()?, path.as_ref().to_path_buf());
+        self.check_path(&from)?;
+        Self::ensure_writable(&from, self.active_editor()?)?;
+        self.check_target(&from, &to, force)?;
+
+        if from.exists() {
+            fs::rename(&from, &to)?;
+        }
+        self.rekey(&from, &to);
+        self.move_side_files(&from, &to)
+    }
+
+    /// 把活跃文件的内容另存一份并打开，活跃文件不变
+    pub fn duplicate(&mut self, path: impl AsRef<Path>, force: bool) -> AppResult<()> {
+        let (from, to) = (self.active_path()?, path.as_ref().to_path_buf());
+        self.check_target(&from, &to, force)?;
+
+        let src = self.active_editor()?;
+        let mut copy = Editor::new();
+        copy.restore_lines(src.all_lines().to_vec(), src.has_final_newline());
+        copy.set_indent_config(src.indent_config());
+        copy.set_save_hooks(src.save_hooks());
+        copy.set_logging(src.logging_enabled());
+        Self::run_save_hooks(&mut copy, self.save_hooks)?;
+        copy.save_to(&to, self.backup)?;
+
+        self.drop_editor(&to);
+        self.editors.insert(to.clone(), copy);
+        self.snapshot(&to);
+        Ok(())
+    }
+
+    /// 另存 / 改名 / 复制的目标检查：沙箱、查看模式，以及已存在（磁盘上或已打开）时需要 force
+    fn check_target(&self, from: &Path, to: &Path, force: bool) -> AppResult<()> {
+        self.check_path(to)?;
+        if self.view_only {
+            return Err(AppError::ReadOnly("view mode, can't create files".into()));
+        }
+        if from == to {
+            return Err(AppError::InvalidArgs("source and target are the same file".into()));
+        }
+        if to.is_dir() {
+            return Err(AppError::InvalidArgs(format!("{} is a directory", to.display())));
+        }
+        // --force 只能覆盖磁盘文件，不能顺带丢掉目标缓冲区里未保存的修改
+        self.ensure_unmodified(&[to.to_path_buf()])?;
+        if !force && (to.exists() || self.editors.contains_key(to)) {
+            return Err(AppError::InvalidArgs(format!(
+                "{} already exists (use --force to overwrite)",
+                to.display()
+            )));
+        }
+        Ok(())
+    }
+
+    /// 把 Editor 挂到新路径下，目标上原有的 Editor 被丢弃；活跃文件指针随之移动
+    fn rekey(&mut self, from: &Path, to: &Path) {
+        let Some(ed) = self.editors.remove(from) else { return };
+        self.remove_swap(from);
+        self.drop_editor(to);
+        self.editors.insert(to.to_path_buf(), ed);
+        if self.is_active_equal_to(from) {
+            self.active = Some(to.to_path_buf());
+        }
+    }
+
+    /// 关闭某个路径上的 Editor（如果有），放弃其未保存的修改
+    fn drop_editor(&mut self, path: &Path) {
+        if self.editors.remove(path).is_some() {
+            self.remove_swap(path);
+            if self.is_active_equal_to(path) {
+                self.active = None;
+            }
+        }
+    }
+
+    /// 改名后把日志文件和本地历史目录一起移过去；目标上已有的不覆盖
+    fn move_side_files(&self, from: &Path, to: &Path) -> AppResult<()> {
+        let pairs = [
+            (logging::log_path_for(&self.base_dir, from), logging::log_path_for(&self.base_dir, to)),
+            (history::dir_for(&self.base_dir, from), history::dir_for(&self.base_dir, to)),
+        ];
+        for (old, new) in pairs {
+            if old != new && old.exists() && !new.exists() {
+                fs::rename(old, new)?;
+            }
+        }
+        Ok(())
+    }
+
+    //  文件系统操作：mkdir / rm / mv / cp。查看模式下一律拒绝；
+    //  涉及已打开文件时，有未保存修改就拒绝，其余的 Editor 跟着调整
+    pub fn make_dir(&self, path: impl AsRef<Path>, parents: bool) -> AppResult<()> {
+        let path = path.as_ref();
+        self.check_fs_op(&[path])?;
+        if parents {
+            fs::create_dir_all(path)?;
+        } else {
+            fs::create_dir(path)?;
+        }
         Ok(())
     }
 
-    pub fn save_all(&mut self) -> AppResult<()> {
-        for (p, ed) in self.editors.iter_mut() {
-            ed.save_to(p)?;
+    /// 移进回收站，返回回收站条目 id；其下已打开的文件随之关闭
+    pub fn remove_path(&mut self, path: impl AsRef<Path>, recursive: bool) -> AppResult<String> {
+        let path = path.as_ref();
+        self.check_fs_op(&[path])?;
+        let meta = fs::symlink_metadata(path)
+            .map_err(|_| AppError::InvalidArgs(format!("no such file or directory: {}", path.display())))?;
+        if meta.is_dir() && !recursive {
+            return Err(AppError::InvalidArgs(format!("{} is a directory (use rm -r)", path.display())));
+        }
+        if self.base_dir.starts_with(path) {
+            return Err(AppError::InvalidArgs("refusing to remove the workspace root".into()));
+        }
+        if path.starts_with(self.base_dir.join(TRASH_DIR)) {
+            return Err(AppError::InvalidArgs(format!("{} is already in the trash", path.display())));
+        }
+
+        let open = self.editors_under(path);
+        self.ensure_unmodified(&open)?;
+        let id = trash::put(&self.base_dir, path)?;
+        for p in &open {
+            self.drop_editor(p);
+        }
+        Ok(id)
+    }
+
+    /// 列出回收站，最新的在前
+    pub fn trash_list(&self) -> AppResult<Vec<TrashEntry>> {
+        trash::list(&self.base_dir)
+    }
+
+    /// 把回收站条目放回原处，返回原路径
+    pub fn restore_trash(&self, id: &str) -> AppResult<PathBuf> {
+        let entry = trash::find(&self.base_dir, id)?;
+        self.check_fs_op(&[&entry.origin])?;
+        trash::restore(&self.base_dir, &entry)?;
+        Ok(entry.origin)
+    }
+
+    /// 移动文件或目录，返回最终路径。to 是已有目录时移到它里面。
+    /// 其下已打开的文件改挂到新路径，日志和本地历史一起移过去。
+    pub fn move_path(&mut self, from: impl AsRef<Path>, to: impl AsRef<Path>, force: bool) -> AppResult<PathBuf> {
+        let from = from.as_ref();
+        let to = self.fs_target(from, to.as_ref(), force)?;
+
+        let moved = self.editors_under(from);
+        self.ensure_unmodified(&moved)?;
+        for p in &moved {
+            Self::ensure_writable(p, &self.editors[p])?;
+        }
+        let overwritten = self.editors_under(&to);
+        self.ensure_unmodified(&overwritten)?;
+
+        trash::move_path(from, &to)?;
+        let mut moved_to = Vec::with_capacity(moved.len());
+        for old in &moved {
+            let rel = old.strip_prefix(from).unwrap_or(Path::new(""));
+            let new = if rel.as_os_str().is_empty() { to.clone() } else { to.join(rel) };
+            self.rekey(old, &new);
+            self.move_side_files(old, &new)?;
+            // 跨文件系统移动时修改时间会变，文件内容没变，这里重新记录
+            if let Some(ed) = self.editors.get_mut(&new) {
+                ed.set_disk_stamp(DiskStamp::of_file(&new)?);
+            }
+            moved_to.push(new);
+        }
+        self.refresh_overwritten(&overwritten, &moved_to)?;
+        Ok(to)
+    }
+
+    /// 复制文件或目录（目录需要 recursive），返回最终路径；被覆盖的已打开文件重新加载
+    pub fn copy_path(
+        &mut self,
+        from: impl AsRef<Path>,
+        to: impl AsRef<Path>,
+        force: bool,
+        recursive: bool,
+    ) -> AppResult<PathBuf> {
+        let from = from.as_ref();
+        if from.is_dir() && !recursive {
+            return Err(AppError::InvalidArgs(format!("{} is a directory (use cp -r)", from.display())));
+        }
+        let to = self.fs_target(from, to.as_ref(), force)?;
+        let overwritten = self.editors_under(&to);
+        self.ensure_unmodified(&overwritten)?;
+
+        trash::copy_tree(from, &to)?;
+        self.refresh_overwritten(&overwritten, &[])?;
+        Ok(to)
+    }
+
+    fn check_fs_op(&self, paths: &[&Path]) -> AppResult<()> {
+        if self.view_only {
+            return Err(AppError::ReadOnly("view mode, can't change files".into()));
+        }
+        for p in paths {
+            self.check_path(p)?;
         }
         Ok(())
     }
 
+    /// mv / cp 的目标：to 是已有目录时放到它里面；已存在时需要 force，目录不覆盖
+    fn fs_target(&self, from: &Path, to: &Path, force: bool) -> AppResult<PathBuf> {
+        if fs::symlink_metadata(from).is_err() {
+            return Err(AppError::InvalidArgs(format!("no such file or directory: {}", from.display())));
+        }
+        let to = match (to.is_dir(), from.file_name()) {
+            (true, Some(name)) => to.join(name),
+            _ => to.to_path_buf(),
+        };
+        self.check_fs_op(&[from, &to])?;
+        if to == from {
+            return Err(AppError::InvalidArgs("source and target are the same file".into()));
+        }
+        if from.is_dir() && to.starts_with(from) {
+            return Err(AppError::InvalidArgs(format!("can't put {} inside itself", from.display())));
+        }
+        if fs::symlink_metadata(&to).is_ok() {
+            if to.is_dir() {
+                return Err(AppError::InvalidArgs(format!("{} is an existing directory", to.display())));
+            }
+            if !force {
+                return Err(AppError::InvalidArgs(format!(
+                    "{} already exists (use --force to overwrite)",
+                    to.display()
+                )));
+            }
+        }
+        Ok(to)
+    }
+
+    /// path 本身以及其下已打开的文件
+    fn editors_under(&self, path: &Path) -> Vec<PathBuf> {
+        self.editors.keys().filter(|p| p.starts_with(path)).cloned().collect()
+    }
+
+    fn ensure_unmodified(&self, paths: &[PathBuf]) -> AppResult<()> {
+        for p in paths {
+            if self.editors.get(p).is_some_and(|ed| ed.is_modified()) {
+                return Err(AppError::InvalidArgs(format!(
+                    "{} has unsaved changes (save or close it first)",
+                    p.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// 磁盘内容被 mv / cp 覆盖的已打开文件：没有被移过来的 Editor 取代的，重新加载
+    fn refresh_overwritten(&mut self, overwritten: &[PathBuf], moved_to: &[PathBuf]) -> AppResult<()> {
+        for p in overwritten {
+            if !moved_to.contains(p) && self.editors.contains_key(p) {
+                // 已确认没有未保存修改，force 只用来放行二进制 / 超大文件
+                self.reload(p, true)?;
+            }
+        }
+        Ok(())
+    }
+
+    //  路径沙箱
+    pub fn set_sandbox(&mut self, sandbox: Option<Sandbox>) {
+        self.sandbox = sandbox;
+    }
+
+    pub fn sandbox(&self) -> Option<&Sandbox> {
+        self.sandbox.as_ref()
+    }
+
+    /// 沙箱开启时检查路径是否在允许的目录内；未开启时总是通过
+    pub fn check_path(&self, path: &Path) -> AppResult<()> {
+        match &self.sandbox {
+            Some(sb) => sb.check(path).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    //  交换文件
+    /// 把有未保存修改、且内容有变化的文件写入交换文件；已保存的文件删除其交换文件。
+    /// 每条命令执行后调用。尚未处理的旧交换文件不会被覆盖。
+    pub fn write_swaps(&mut self) -> AppResult<()> {
+        let mut clean = Vec::new();
+        for (path, ed) in &self.editors {
+            if self.pending_swaps.contains_key(path) {
+                continue;
+            }
+            if !ed.is_modified() {
+                if self.swapped.contains_key(path) {
+                    clean.push(path.clone());
+                }
+                continue;
+            }
+            if self.swapped.get(path) == Some(&ed.revision()) {
+                continue;
+            }
+            let swap = SwapFile::new(path, ed.all_lines().to_vec(), ed.has_final_newline());
+            swap.write(&SwapFile::path_for(&self.base_dir, path))?;
+            self.swapped.insert(path.clone(), ed.revision());
+        }
+        for path in clean {
+            self.remove_swap(&path);
+        }
+        Ok(())
+    }
+
+    /// 扫描 base_dir 下遗留的交换文件，返回提示信息（每个文件一行）
+    pub fn scan_swaps(&mut self) -> Vec<String> {
+        let mut notes = Vec::new();
+        for (swap_path, swap) in SwapFile::scan(&self.base_dir) {
+            match swap {
+                Ok(swap) => {
+                    notes.push(format!(
+                        "unsaved changes for {} found (swap written {}); run `recover` or `discard`",
+                        swap.path, swap.saved_at
+                    ));
+                    self.pending_swaps.insert(PathBuf::from(swap.path), swap_path);
+                }
+                Err(e) => notes.push(format!(
+                    "ignoring unreadable swap file {}: {}",
+                    swap_path.display(),
+                    e
+                )),
+            }
+        }
+        notes
+    }
+
+    pub fn pending_swaps(&self) -> Vec<PathBuf> {
+        self.pending_swaps.keys().cloned().collect()
+    }
+
+    /// 用交换文件恢复内容：文件未打开时先打开，恢复后设为活跃文件
+    pub fn recover(&mut self, path: impl AsRef<Path>) -> AppResult<()> {
+        let key = path.as_ref().to_path_buf();
+        let swap_path = self
+            .pending_swaps
+            .get(&key)
+            .ok_or_else(|| AppError::InvalidArgs(format!("no swap file for {}", key.display())))?;
+        let swap = SwapFile::read(swap_path)?;
+
+        if !self.editors.contains_key(&key) {
+            self.load(&key)?;
+        }
+        let ed = self
+            .editors
+            .get_mut(&key)
+            .ok_or_else(|| AppError::InternalError("recovered editor not found".into()))?;
+        Self::ensure_writable(&key, ed)?;
+        ed.restore_lines(swap.lines, swap.final_newline);
+
+        // 交换文件保留到下一次 write_swaps 用当前内容覆盖
+        self.pending_swaps.remove(&key);
+        self.active = Some(key);
+        Ok(())
+    }
+
+    /// 放弃交换文件里的内容，直接删除
+    pub fn discard_swap(&mut self, path: impl AsRef<Path>) -> AppResult<()> {
+        let key = path.as_ref();
+        let swap_path = self
+            .pending_swaps
+            .remove(key)
+            .ok_or_else(|| AppError::InvalidArgs(format!("no swap file for {}", key.display())))?;
+        fs::remove_file(swap_path)?;
+        Ok(())
+    }
+
+    pub fn backup_mode(&self) -> BackupMode {
+        self.backup
+    }
+
+    pub fn set_backup_mode(&mut self, backup: BackupMode) {
+        self.backup = backup;
+    }
+
+    /// 工作区级别的保存钩子
+    pub fn save_hooks(&self) -> SaveHooks {
+        self.save_hooks
+    }
+
+    pub fn set_save_hooks(&mut self, hooks: SaveHooks) {
+        self.save_hooks = hooks;
+    }
+
+    /// 设置当前文件自己的保存钩子，None 表示恢复为工作区默认
+    pub fn set_active_save_hooks(&mut self, hooks: Option<SaveHooks>) -> AppResult<()> {
+        let ed = self.get_active_editor_mut()?;
+        ed.set_save_hooks(hooks);
+        Ok(())
+    }
+
+    //  位置与书签（作用于活跃文件）
+    pub fn set_view(&mut self, view: Option<(usize, usize)>) -> AppResult<()> {
+        self.get_active_editor_mut()?.set_view(view);
+        Ok(())
+    }
+
+    /// 设置当前位置；行号超出文件时报错，列号不做限制（行内容随时会变）
+    pub fn set_position(&mut self, pos: Position) -> AppResult<()> {
+        let ed = self.get_active_editor_mut()?;
+        Self::check_position(ed, pos)?;
+        ed.set_position(Some(pos));
+        Ok(())
+    }
+
+    /// 书签位置的检查与 set_position 相同
+    pub fn set_bookmark(&mut self, name: &str, pos: Position) -> AppResult<()> {
+        let ed = self.get_active_editor_mut()?;
+        Self::check_position(ed, pos)?;
+        ed.set_bookmark(name, pos);
+        Ok(())
+    }
+
+    fn check_position(ed: &Editor, pos: Position) -> AppResult<()> {
+        if pos.line == 0 || pos.col == 0 || pos.line > ed.count_lines().max(1) {
+            return Err(AppError::InvalidArgs(format!("position {} out of range", pos)));
+        }
+        Ok(())
+    }
+
+    pub fn remove_bookmark(&mut self, name: &str) -> AppResult<()> {
+        if !self.get_active_editor_mut()?.remove_bookmark(name) {
+            return Err(AppError::InvalidArgs(format!("no such bookmark: {}", name)));
+        }
+        Ok(())
+    }
+
+    //  寄存器
+    /// 写入寄存器，同时更新无名寄存器。只有 a-z、0-9 和无名寄存器可以直接写。
+    pub fn set_register(&mut self, name: char, reg: Register) -> AppResult<()> {
+        if !(name == UNNAMED_REGISTER || name.is_ascii_lowercase() || name.is_ascii_digit()) {
+            return Err(AppError::InvalidArgs(format!("register {} is not writable", name)));
+        }
+        self.registers.insert(name, reg.clone());
+        self.registers.insert(UNNAMED_REGISTER, reg);
+        Ok(())
+    }
+
+    pub fn get_register(&self, name: char) -> AppResult<&Register> {
+        self.registers
+            .get(&name)
+            .ok_or_else(|| AppError::InvalidArgs(format!("register {} is empty", name)))
+    }
+
+    /// 记录被删除的文本，delete / replace 等命令执行成功后调用。
+    pub fn record_deleted(&mut self, reg: Register) {
+        self.registers.insert(DELETED_REGISTER, reg.clone());
+        self.registers.insert(UNNAMED_REGISTER, reg);
+    }
+
+    pub fn list_registers(&self) -> String {
+        let mut out = String::new();
+        for (name, reg) in &self.registers {
+            let text = match reg {
+                Register::Chars(s) => format!("{:?}", s),
+                Register::Lines(lines) => format!("{} line(s) {:?}", lines.len(), lines.join("\n")),
+            };
+            let _ = writeln!(&mut out, "\"{}  {}", name, text);
+        }
+        out
+    }
+
+    /// 只读访问当前活跃的 Editor，供命令在构造 DocCommand 前读取内容。
+    pub fn active_editor(&self) -> AppResult<&Editor> {
+        let path = self
+            .active
+            .as_ref()
+            .ok_or_else(|| AppError::InternalError("no active file.".into()))?;
+
+        self.editors
+            .get(path)
+            .ok_or_else(|| AppError::InvalidArgs("active editor not found".into()))
+    }
+
+    pub fn editor(&self, path: impl AsRef<Path>) -> Option<&Editor> {
+        self.editors.get(path.as_ref())
+    }
+
     pub fn active_file_path(&self) -> Option<PathBuf> {
         self.active.clone()
     }
@@ -191,34 +914,125 @@ impl Workspace {
             .map(|editor| editor.is_modified())  // 提取 modified 字段
     }
 
-    pub fn from_memento(&mut self, m: WorkspaceMemento) -> AppResult<()> {
-        self.editors.clear();
+    // 名字沿用 Memento 模式的习惯叫法，这里是“从快照恢复到自身”。
+    /// 读不出来的文件跳过，不影响其余文件；返回需要提示用户的警告
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_memento(&mut self, m: WorkspaceMemento) -> AppResult<Vec<String>> {
+        // 与 close 一样，丢弃的 Editor 连同未保存的修改一起放弃，交换文件随之删除
+        let discarded: Vec<PathBuf> = self.editors.drain().map(|(p, _)| p).collect();
+        for p in discarded {
+            self.remove_swap(&p);
+        }
         self.active = None;
+        let mut warnings = Vec::new();
 
         for (path_str, flags) in m.open_files {
-            let path = PathBuf::from(&path_str);
-            let content = match fs::read_to_string(&path) {
-                Ok(s) => s,
-                Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
-                Err(e) => return Err(AppError::Io(e)),
+            // 旧快照里可能是相对于当时 cwd 的路径，统一转成绝对路径
+            let path = std::path::absolute(&path_str)?;
+            if let Err(e) = self.check_path(&path) {
+                warnings.push(format!("skipped {}", e));
+                continue;
+            }
+            let content = match read_text(&path, self.max_file_size, false) {
+                Ok((s, _)) => s,
+                Err(e) => {
+                    warnings.push(format!("skipped {}: {}", path_str, e));
+                    continue;
+                }
             };
 
             let mut editor = Editor::new();
             editor.load_from(&content);
+            editor.set_disk_stamp(DiskStamp::of_file(&path)?);
             editor.set_modified(flags.modified);
             editor.set_logging(flags.logging);
+            editor.set_view(flags.view);
+            editor.set_position(flags.position);
+            editor.set_bookmarks(flags.bookmarks);
+            editor.set_readonly(flags.readonly);
+            editor.set_locked(self.view_only || !is_writable(&path));
 
             self.editors.insert(path, editor);
         }
 
         if let Some(active_str) = m.active {
-            let active_path = PathBuf::from(&active_str);
+            let active_path = std::path::absolute(&active_str)?;
             if self.editors.contains_key(&active_path) {
                 self.active = Some(active_path);
             }
         }
 
-        Ok(())
+        self.registers = m.registers;
+
+        Ok(warnings)
+    }
+
+    //  会话：完整的工作区快照，按名字存放在 base_dir/.sessions 下
+    fn session_path(&self, name: &str) -> AppResult<PathBuf> {
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(AppError::InvalidArgs(format!(
+                "invalid session name: {} (letters, digits, '-', '_' and '.' only)",
+                name
+            )));
+        }
+        Ok(self.base_dir.join(SESSIONS_DIR).join(format!("{}.json", name)))
+    }
+
+    pub fn save_session(&self, name: &str) -> AppResult<()> {
+        let path = self.session_path(name)?;
+        fs::create_dir_all(self.base_dir.join(SESSIONS_DIR))?;
+        self.to_memento().save(&path)
+    }
+
+    /// 切换到指定会话。当前有未保存的修改时需要 force，否则这些修改会丢失。
+    pub fn load_session(&mut self, name: &str, force: bool) -> AppResult<Vec<String>> {
+        let path = self.session_path(name)?;
+        if !path.exists() {
+            return Err(AppError::InvalidArgs(format!("no such session: {}", name)));
+        }
+        if !force && let Some(p) = self.editors.iter().find(|(_, e)| e.is_modified()).map(|(p, _)| p) {
+            return Err(AppError::InvalidArgs(format!(
+                "{} has unsaved changes (save first or use session-load {} --force)",
+                p.display(),
+                name
+            )));
+        }
+        let (m, _) = WorkspaceMemento::load(&path)?;
+        self.from_memento(m)
+    }
+
+    pub fn delete_session(&self, name: &str) -> AppResult<()> {
+        let path = self.session_path(name)?;
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                Err(AppError::InvalidArgs(format!("no such session: {}", name)))
+            }
+            r => Ok(r?),
+        }
+    }
+
+    /// 已保存的会话名与保存时间，按名字排序
+    pub fn list_sessions(&self) -> AppResult<Vec<(String, SystemTime)>> {
+        let Ok(entries) = fs::read_dir(self.base_dir.join(SESSIONS_DIR)) else { return Ok(Vec::new()) };
+        let mut sessions = Vec::new();
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json")
+                && let Some(stem) = path.file_stem()
+            {
+                let mtime = entry.metadata()?.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                sessions.push((stem.to_string_lossy().into_owned(), mtime));
+            }
+        }
+        sessions.sort();
+        Ok(sessions)
+    }
+
+    pub fn editor_count(&self) -> usize {
+        self.editors.len()
     }
 
     pub fn to_memento(&self) -> WorkspaceMemento {
@@ -230,15 +1044,21 @@ impl Workspace {
                 FileFlags {
                     modified: e.is_modified(),
                     logging: e.logging_enabled(),
+                    view: e.view(),
+                    position: e.position(),
+                    bookmarks: e.bookmarks().clone(),
+                    readonly: e.explicit_readonly(),
                 },
             );
         }
         WorkspaceMemento {
+            version: MEMENTO_VERSION,
             open_files,
             active: self
                 .active
                 .as_ref()
                 .map(|p| p.to_string_lossy().into_owned()),
+            registers: self.registers.clone(),
         }
     }
 
@@ -292,16 +1112,13 @@ impl Workspace {
         let p = path.as_ref();
 
         // 拿到文件名，决定日志文件名
-        let file_name = p
-            .file_name()
-            .ok_or_else(|| AppError::InvalidArgs(format!(
+        if p.file_name().is_none() {
+            return Err(AppError::InvalidArgs(format!(
                 "invalid file path for log-show: {}",
                 p.display()
-            )))?
-            .to_string_lossy()
-            .into_owned();
-
-        let log_path = self.base_dir.join(format!(".{}.log", file_name));
+            )));
+        }
+        let log_path = logging::log_path_for(&self.base_dir, p);
 
         if !log_path.exists() {
             return Err(AppError::InvalidArgs(format!(
@@ -315,6 +1132,12 @@ impl Workspace {
     }
 
     // 辅助函数
+    fn active_path(&self) -> AppResult<PathBuf> {
+        self.active
+            .clone()
+            .ok_or_else(|| AppError::InternalError("no active file.".into()))
+    }
+
     fn get_active_editor_mut(&mut self) -> AppResult<&mut Editor> {
         let path = self
             .active
@@ -326,11 +1149,45 @@ impl Workspace {
             .ok_or_else(|| AppError::InvalidArgs("active editor not found".into()))
     }
 
-    fn is_active_equal_to(&self, borrowed_path: &PathBuf) -> bool {
-        self.active
-            .as_ref()           // Option<&PathBuf>
-            .map(|pb| pb.as_path()) // Option<&Path>
-            .map_or(false, |active_path| active_path == borrowed_path.as_path())
+    /// 活跃文件，且要求可写
+    fn get_writable_editor_mut(&mut self) -> AppResult<&mut Editor> {
+        let path = self.active_path()?;
+        let ed = self.get_active_editor_mut()?;
+        Self::ensure_writable(&path, ed)?;
+        Ok(ed)
+    }
+
+    fn ensure_writable(path: &Path, ed: &Editor) -> AppResult<()> {
+        if ed.is_readonly() {
+            return Err(AppError::ReadOnly(path.display().to_string()));
+        }
+        Ok(())
+    }
+
+    fn remove_swap(&mut self, path: &Path) {
+        self.swapped.remove(path);
+        let _ = fs::remove_file(SwapFile::path_for(&self.base_dir, path));
+    }
+
+    /// 磁盘上的文件在加载后被外部修改时报错
+    fn check_disk(path: &Path, ed: &Editor) -> AppResult<()> {
+        if DiskStamp::changed_since(ed.disk_stamp(), path)? {
+            return Err(AppError::ChangedOnDisk(path.display().to_string()));
+        }
+        Ok(())
+    }
+
+    /// 写盘前按钩子整理内容；文件有自己的设置时优先使用
+    fn run_save_hooks(ed: &mut Editor, default: SaveHooks) -> AppResult<()> {
+        let hooks = ed.save_hooks().unwrap_or(default);
+        if let Some(cmd) = hooks.cleanup_for(ed) {
+            ed.exec_doc(Box::new(cmd))?;
+        }
+        Ok(())
+    }
+
+    fn is_active_equal_to(&self, borrowed_path: &Path) -> bool {
+        self.active.as_deref() == Some(borrowed_path)
     }
 
     fn write_editor(p: impl AsRef<Path>, is_active: bool, modified: bool) -> String {
@@ -351,6 +1208,56 @@ impl Workspace {
     }
 }
 
+/// 当前用户能否写这个文件；以写方式打开一次（不截断，不改内容）来判断，
+/// 比只看权限位准确。文件不存在时视为可写（保存时新建）。
+fn is_writable(path: &Path) -> bool {
+    match fs::OpenOptions::new().write(true).open(path) {
+        Ok(_) => true,
+        Err(e) => e.kind() == io::ErrorKind::NotFound,
+    }
+}
+
+/// 读取文本文件，返回内容以及是否做过有损转换；文件不存在时为空。
+/// limit 为大小上限；不加 force 时含 NUL 字节或不是合法 UTF-8 的文件当作二进制文件拒绝。
+fn read_text(path: &Path, limit: Option<u64>, force: bool) -> AppResult<(String, bool)> {
+    let size = match fs::metadata(path) {
+        Ok(meta) => meta.len(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((String::new(), false)),
+        Err(e) => return Err(AppError::Io(e)),
+    };
+    if let Some(limit) = limit
+        && size > limit
+    {
+        return Err(AppError::FileTooLarge(format!(
+            "{} is {} bytes, limit is {}",
+            path.display(),
+            size,
+            limit
+        )));
+    }
+
+    let data = fs::read(path)?;
+    if !force && let Some(pos) = data.iter().position(|&b| b == 0) {
+        return Err(AppError::BinaryFile(format!("{}: NUL byte at offset {}", path.display(), pos)));
+    }
+    match String::from_utf8(data) {
+        Ok(s) => Ok((s, false)),
+        Err(e) if force => Ok((String::from_utf8_lossy(e.as_bytes()).into_owned(), true)),
+        Err(e) => Err(AppError::BinaryFile(format!(
+            "{}: not valid UTF-8 at offset {}",
+            path.display(),
+            e.utf8_error().valid_up_to()
+        ))),
+    }
+}
+
+/// 按 Editor 加载文件的规则把文本拆成行，并给出是否以换行结尾
+fn split_content(content: &str) -> (Vec<String>, bool) {
+    let mut tmp = Editor::new();
+    tmp.load_from(content);
+    (tmp.all_lines().to_vec(), tmp.has_final_newline())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -362,10 +1269,8 @@ mod tests {
     fn new_temp_workspace() -> (Workspace, tempfile::TempDir) {
         let tmp = tempdir().expect("create tempdir failed");
 
-        let mut ws = Workspace::default();
-        // 这里直接改私有字段没问题，因为 tests 是同一模块的子模块
-        ws.base_dir = tmp.path().join("work_dir");
-        fs::create_dir_all(&ws.base_dir).expect("create work_dir failed");
+        let ws = Workspace::new(tmp.path().join("work_dir"));
+        assert!(ws.base_dir.is_dir(), "create work_dir failed");
 
         (ws, tmp)
     }
@@ -403,7 +1308,7 @@ mod tests {
         ws.active = Some(file_path.clone());
 
         // 调用 save_file
-        ws.save_file(&file_path).expect("save_file failed");
+        ws.save_file(&file_path, false).expect("save_file failed");
 
         // 磁盘上应该出现 base_dir/foo.txt，内容为 "hello workspace"
         let content = fs::read_to_string(&file_path).expect("read saved file failed");
@@ -430,7 +1335,7 @@ mod tests {
         ed_b.append_line("BBBB");
         ws.editors.insert(file_b.clone(), ed_b);
 
-        ws.save_all().expect("save_all failed");
+        ws.save_all(false).expect("save_all failed");
 
         let content_a = fs::read_to_string(&file_a).expect("read a.txt failed");
         let content_b = fs::read_to_string(&file_b).expect("read b.txt failed");
@@ -439,6 +1344,460 @@ mod tests {
         assert_eq!(content_b.trim_end(), "BBBB");
     }
 
+    #[test]
+    fn save_hooks_clean_buffer_before_writing() {
+        let (mut ws, _tmp) = new_temp_workspace();
+        let file_path = ws.resolve_path(Some("hooks.txt"));
+        fs::write(&file_path, "a  \n\n\nb").unwrap();
+        ws.load(&file_path).unwrap();
+
+        ws.set_save_hooks(SaveHooks { strip_trailing: true, ..Default::default() });
+        // 文件自己的设置覆盖工作区默认值
+        ws.set_active_save_hooks(Some(SaveHooks {
+            collapse_blank: true,
+            final_newline: true,
+            ..Default::default()
+        }))
+        .unwrap();
+        ws.save_file(&file_path, false).unwrap();
+
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "a  \n\nb\n");
+        assert_eq!(ws.show(None, None).unwrap(), "1: a  \n2: \n3: b\n");
+
+        // 整理本身可以撤销
+        ws.undo().unwrap();
+        assert_eq!(ws.show(None, None).unwrap(), "1: a  \n2: \n3: \n4: b\n");
+    }
+
+    #[test]
+    fn swap_files_follow_unsaved_changes_and_can_be_recovered() {
+        let (mut ws, _tmp) = new_temp_workspace();
+        let file_path = ws.resolve_path(Some("swap.txt"));
+        fs::write(&file_path, "on disk").unwrap();
+        ws.load(&file_path).unwrap();
+
+        // 未修改：不写交换文件
+        ws.write_swaps().unwrap();
+        let swap_path = SwapFile::path_for(&ws.base_dir, &file_path);
+        assert!(!swap_path.exists());
+
+        ws.get_active_editor_mut().unwrap().restore_lines(vec!["edited".into()], true);
+        ws.write_swaps().unwrap();
+        assert!(swap_path.exists());
+
+        // 模拟崩溃后重启：新的 Workspace 从磁盘加载，发现交换文件
+        let mut ws2 = Workspace::new(ws.base_dir.clone());
+        ws2.load(&file_path).unwrap();
+        assert_eq!(ws2.scan_swaps().len(), 1);
+        assert_eq!(ws2.pending_swaps(), vec![file_path.clone()]);
+
+        ws2.recover(&file_path).unwrap();
+        assert_eq!(ws2.show(None, None).unwrap(), "1: edited\n");
+        assert_eq!(ws2.active_modified(), Some(true));
+
+        // 保存后交换文件被清理
+        ws2.write_swaps().unwrap();
+        ws2.save_file(&file_path, false).unwrap();
+        ws2.write_swaps().unwrap();
+        assert!(!swap_path.exists());
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "edited\n");
+    }
+
+    #[test]
+    fn save_refuses_when_file_changed_on_disk() {
+        let (mut ws, _tmp) = new_temp_workspace();
+        let file_path = ws.resolve_path(Some("ext.txt"));
+        fs::write(&file_path, "v1\n").unwrap();
+        ws.load(&file_path).unwrap();
+        ws.get_active_editor_mut().unwrap().append_line("mine");
+
+        // 只 touch 不改内容不算修改
+        fs::write(&file_path, "v1\n").unwrap();
+        ws.save_file(&file_path, false).unwrap();
+
+        fs::write(&file_path, "from elsewhere\n").unwrap();
+        let err = ws.save_file(&file_path, false).unwrap_err();
+        assert_eq!(err.code(), 2005);
+        assert!(ws.save_all(false).is_err());
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "from elsewhere\n");
+
+        ws.save_file(&file_path, true).unwrap();
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "v1\nmine\n");
+    }
+
+    #[test]
+    fn reload_replaces_buffer_and_is_undoable() {
+        let (mut ws, _tmp) = new_temp_workspace();
+        let file_path = ws.resolve_path(Some("reload.txt"));
+        fs::write(&file_path, "old\n").unwrap();
+        ws.load(&file_path).unwrap();
+        let ed = ws.get_active_editor_mut().unwrap();
+        ed.append_line("unsaved");
+        ed.set_modified(true);
+
+        fs::write(&file_path, "new\n").unwrap();
+        assert!(ws.reload(&file_path, false).is_err());
+        ws.reload(&file_path, true).unwrap();
+        assert_eq!(ws.show(None, None).unwrap(), "1: new\n");
+        assert_eq!(ws.active_modified(), Some(false));
+        // 重新加载后磁盘状态已刷新，可以正常保存
+        ws.save_file(&file_path, false).unwrap();
+
+        ws.undo().unwrap();
+        assert_eq!(ws.show(None, None).unwrap(), "1: old\n2: unsaved\n");
+    }
+
+    #[test]
+    fn saves_are_snapshotted_and_restorable() {
+        let (mut ws, _tmp) = new_temp_workspace();
+        let file_path = ws.resolve_path(Some("hist.txt"));
+        ws.load(&file_path).unwrap();
+
+        ws.get_active_editor_mut().unwrap().append_line("first");
+        ws.save_file(&file_path, false).unwrap();
+        ws.get_active_editor_mut().unwrap().append_line("second");
+        ws.save_file(&file_path, false).unwrap();
+        // 内容没变的保存不产生新快照
+        ws.save_all(false).unwrap();
+
+        let snaps = ws.history_list(&file_path).unwrap();
+        assert_eq!(snaps.len(), 2);
+        assert!(snaps[0].path.starts_with(ws.base_dir.join(".history")));
+
+        ws.restore_history(&file_path, "2").unwrap();
+        assert_eq!(ws.show(None, None).unwrap(), "1: first\n");
+        ws.undo().unwrap();
+        assert_eq!(ws.show(None, None).unwrap(), "1: first\n2: second\n");
+    }
+
+    #[test]
+    fn history_failure_does_not_fail_the_save() {
+        let (mut ws, _tmp) = new_temp_workspace();
+        let file_path = ws.resolve_path(Some("a.txt"));
+        // .history 被普通文件占住，快照写不进去
+        fs::write(ws.base_dir.join(".history"), "").unwrap();
+        ws.load(&file_path).unwrap();
+        ws.get_active_editor_mut().unwrap().append_line("kept");
+
+        ws.save_file(&file_path, false).unwrap();
+        assert_eq!(fs::read_to_string(&file_path).unwrap().trim_end(), "kept");
+        assert!(!ws.editor(&file_path).unwrap().is_modified());
+        ws.save_all(false).unwrap();
+    }
+
+    #[test]
+    fn sessions_switch_between_sets_of_open_files() {
+        let (mut ws, _tmp) = new_temp_workspace();
+        let a = ws.resolve_path(Some("a.txt"));
+        let b = ws.resolve_path(Some("b.txt"));
+        ws.load(&a).unwrap();
+        ws.save_session("bugfix").unwrap();
+
+        ws.load(&b).unwrap();
+        ws.save_session("feature").unwrap();
+        assert!(ws.save_session("../escape").is_err());
+
+        ws.get_active_editor_mut().unwrap().restore_lines(vec!["edited".into()], true);
+        ws.write_swaps().unwrap();
+        let swap_path = SwapFile::path_for(&ws.base_dir, &b);
+        assert!(swap_path.exists());
+        assert!(ws.load_session("bugfix", false).is_err());
+        ws.load_session("bugfix", true).unwrap();
+        assert!(!swap_path.exists());
+        assert_eq!(ws.editor_count(), 1);
+        assert_eq!(ws.active_file_path(), Some(a.clone()));
+
+        let names: Vec<String> = ws.list_sessions().unwrap().into_iter().map(|(n, _)| n).collect();
+        assert_eq!(names, vec!["bugfix", "feature"]);
+        ws.delete_session("feature").unwrap();
+        assert!(ws.delete_session("feature").is_err());
+        assert!(ws.load_session("feature", false).is_err());
+    }
+
+    #[test]
+    fn positions_and_bookmarks_round_trip_through_memento() {
+        let (mut ws, _tmp) = new_temp_workspace();
+        let file_path = ws.resolve_path(Some("pos.txt"));
+        fs::write(&file_path, "a\nb\nc\n").unwrap();
+        ws.load(&file_path).unwrap();
+
+        ws.set_view(Some((2, 3))).unwrap();
+        ws.set_position(Position { line: 3, col: 2 }).unwrap();
+        assert!(ws.set_position(Position { line: 9, col: 1 }).is_err());
+        ws.set_bookmark("todo", Position { line: 1, col: 1 }).unwrap();
+        assert!(ws.set_bookmark("bad", Position { line: 0, col: 1 }).is_err());
+        assert!(ws.set_bookmark("bad", Position { line: 1, col: 0 }).is_err());
+        assert!(ws.set_bookmark("bad", Position { line: 9, col: 1 }).is_err());
+
+        let json = serde_json::to_string(&ws.to_memento()).unwrap();
+        let (mut restored, _tmp2) = new_temp_workspace();
+        restored.from_memento(serde_json::from_str(&json).unwrap()).unwrap();
+        let ed = restored.active_editor().unwrap();
+        assert_eq!(ed.view(), Some((2, 3)));
+        assert_eq!(ed.position(), Some(Position { line: 3, col: 2 }));
+        assert_eq!(ed.bookmarks().get("todo"), Some(&Position { line: 1, col: 1 }));
+    }
+
+    #[test]
+    fn readonly_files_reject_edits_and_saves() {
+        let (mut ws, _tmp) = new_temp_workspace();
+        let ro = ws.resolve_path(Some("ro.txt"));
+        let rw = ws.resolve_path(Some("rw.txt"));
+        fs::write(&ro, "keep\n").unwrap();
+        ws.load(&rw).unwrap();
+        ws.exec_doc(Box::new(ReplaceContentCommand::new(vec!["new".into()], true))).unwrap();
+        ws.load(&ro).unwrap();
+        ws.set_readonly(&ro, true).unwrap();
+
+        let edit = || Box::new(ReplaceContentCommand::new(vec!["x".into()], true));
+        assert_eq!(ws.exec_doc(edit()).unwrap_err().code(), 2008);
+        assert_eq!(ws.exec_doc_at(&ro, edit()).unwrap_err().code(), 2008);
+        assert_eq!(ws.save_file(&ro, true).unwrap_err().code(), 2008);
+        assert!(ws.show(None, None).is_ok());
+        // 没有修改的只读文件不妨碍保存其他文件
+        ws.save_all(false).unwrap();
+        assert_eq!(fs::read_to_string(&rw).unwrap(), "new\n");
+
+        let json = serde_json::to_string(&ws.to_memento()).unwrap();
+        let (mut restored, _tmp2) = new_temp_workspace();
+        restored.from_memento(serde_json::from_str(&json).unwrap()).unwrap();
+        assert!(restored.editor(&ro).unwrap().is_readonly());
+        assert!(!restored.editor(&rw).unwrap().is_readonly());
+
+        // 查看模式：打开的文件都是只读，也不能新建
+        restored.set_view_only(true);
+        restored.load(&rw).unwrap();
+        assert!(restored.editor(&rw).unwrap().is_readonly());
+        restored.set_readonly(&rw, false).unwrap();
+        assert!(restored.editor(&rw).unwrap().is_readonly());
+        assert_eq!(restored.init(ws.resolve_path(Some("new.txt")), false).unwrap_err().code(), 2008);
+    }
+
+    #[test]
+    fn derived_readonly_is_recomputed_on_load() {
+        let (mut ws, _tmp) = new_temp_workspace();
+        let path = ws.resolve_path(Some("bad.txt"));
+        fs::write(&path, b"ok\n\xff\n").unwrap();
+        ws.load_with(&path, true).unwrap();
+        assert!(ws.editor(&path).unwrap().is_readonly());
+
+        // 文件修好后重新加载，有损转换留下的只读随之解除
+        fs::write(&path, "ok\n").unwrap();
+        ws.load(&path).unwrap();
+        assert!(!ws.editor(&path).unwrap().is_readonly());
+
+        // 用户指定的只读随快照保存，查看模式不会写进快照
+        ws.set_readonly(&path, true).unwrap();
+        ws.set_view_only(true);
+        let json = serde_json::to_string(&ws.to_memento()).unwrap();
+        let (mut restored, _tmp2) = new_temp_workspace();
+        restored.from_memento(serde_json::from_str(&json).unwrap()).unwrap();
+        assert!(restored.editor(&path).unwrap().is_readonly());
+        restored.set_readonly(&path, false).unwrap();
+        assert!(!restored.editor(&path).unwrap().is_readonly());
+    }
+
+    #[test]
+    fn save_as_rename_and_duplicate_rekey_editors() {
+        let (mut ws, _tmp) = new_temp_workspace();
+        let a = ws.resolve_path(Some("a.txt"));
+        let b = ws.resolve_path(Some("b.txt"));
+        let c = ws.resolve_path(Some("c.txt"));
+        let d = ws.resolve_path(Some("d.txt"));
+        fs::write(&a, "one\n").unwrap();
+        fs::write(&b, "other\n").unwrap();
+        ws.load(&a).unwrap();
+        ws.save_file(&a, false).unwrap();
+        fs::write(ws.base_dir.join(".a.txt.log"), "log\n").unwrap();
+        ws.exec_doc(Box::new(ReplaceContentCommand::new(vec!["two".into()], true))).unwrap();
+
+        // 改名：磁盘文件、日志和历史跟着走，未保存的修改留在缓冲区
+        assert!(ws.rename(&b, false).is_err());
+        ws.rename(&c, false).unwrap();
+        assert!(!a.exists());
+        assert_eq!(fs::read_to_string(&c).unwrap(), "one\n");
+        assert_eq!(ws.active_file_path(), Some(c.clone()));
+        assert!(ws.editor(&a).is_none());
+        assert!(ws.editor(&c).unwrap().is_modified());
+        assert!(ws.base_dir.join(".c.txt.log").exists());
+        assert_eq!(ws.history_list(&c).unwrap().len(), 1);
+
+        // 复制：活跃文件不变，副本落盘并打开
+        ws.duplicate(&d, false).unwrap();
+        assert_eq!(fs::read_to_string(&d).unwrap(), "two\n");
+        assert_eq!(ws.active_file_path(), Some(c.clone()));
+        assert!(!ws.editor(&d).unwrap().is_modified());
+
+        // 目标缓冲区有未保存的修改时，--force 也不能覆盖
+        ws.exec_doc_at(&d, Box::new(ReplaceContentCommand::new(vec!["dirty".into()], true))).unwrap();
+        assert!(ws.save_as(&d, true).is_err());
+        assert!(ws.rename(&d, true).is_err());
+        ws.save_file(&d, false).unwrap();
+
+        // 另存：覆盖已打开的 d 需要 force，之后编辑的是 d
+        assert!(ws.save_as(&d, false).is_err());
+        ws.save_as(&d, true).unwrap();
+        assert_eq!(ws.active_file_path(), Some(d.clone()));
+        assert!(ws.editor(&c).is_none());
+        assert_eq!(ws.editor_count(), 1);
+        assert_eq!(fs::read_to_string(&c).unwrap(), "one\n");
+        assert!(!ws.editor(&d).unwrap().is_modified());
+    }
+
+    #[test]
+    fn file_operations_keep_open_editors_consistent() {
+        let (mut ws, _tmp) = new_temp_workspace();
+        let dir = ws.resolve_path(Some("src"));
+        ws.make_dir(dir.join("deep"), true).unwrap();
+        let inner = dir.join("deep/a.txt");
+        let other = ws.resolve_path(Some("b.txt"));
+        fs::write(&inner, "a\n").unwrap();
+        fs::write(&other, "b\n").unwrap();
+        ws.load(&other).unwrap();
+        ws.load(&inner).unwrap();
+
+        // 有未保存修改时不能移动
+        ws.exec_doc(Box::new(ReplaceContentCommand::new(vec!["x".into()], true))).unwrap();
+        assert!(ws.move_path(&dir, ws.resolve_path(Some("lib")), false).is_err());
+        ws.undo().unwrap();
+        ws.get_active_editor_mut().unwrap().set_modified(false);
+
+        let lib = ws.resolve_path(Some("lib"));
+        assert_eq!(ws.move_path(&dir, &lib, false).unwrap(), lib);
+        let moved = lib.join("deep/a.txt");
+        assert_eq!(ws.active_file_path(), Some(moved.clone()));
+        assert!(ws.editor(&inner).is_none());
+
+        // 覆盖已打开的文件：需要 force，之后重新加载
+        assert!(ws.copy_path(&moved, &other, false, false).is_err());
+        ws.copy_path(&moved, &other, true, false).unwrap();
+        assert_eq!(ws.editor(&other).unwrap().all_lines(), ["a"]);
+        assert!(ws.copy_path(&lib, ws.resolve_path(Some("lib2")), false, false).is_err());
+        ws.copy_path(&lib, ws.resolve_path(Some("lib2")), false, true).unwrap();
+        assert!(ws.resolve_path(Some("lib2/deep/a.txt")).exists());
+
+        // rm 进回收站，已打开的文件随之关闭，可以放回
+        assert!(ws.remove_path(&lib, false).is_err());
+        let id = ws.remove_path(&lib, true).unwrap();
+        assert!(!lib.exists());
+        assert!(ws.editor(&moved).is_none());
+        assert_eq!(ws.active_file_path(), None);
+        assert!(ws.remove_path(ws.base_dir.clone(), true).is_err());
+        assert_eq!(ws.restore_trash(&id).unwrap(), lib);
+        assert!(moved.exists());
+    }
+
+    #[test]
+    fn binary_and_oversized_files_need_force() {
+        let (mut ws, _tmp) = new_temp_workspace();
+        let nul = ws.resolve_path(Some("nul.bin"));
+        let latin1 = ws.resolve_path(Some("latin1.txt"));
+        let big = ws.resolve_path(Some("big.txt"));
+        fs::write(&nul, b"a\0b\n").unwrap();
+        fs::write(&latin1, b"caf\xe9\n").unwrap();
+        fs::write(&big, "0123456789\n").unwrap();
+
+        assert_eq!(ws.load(&nul).unwrap_err().code(), 2009);
+        assert_eq!(ws.load(&latin1).unwrap_err().code(), 2009);
+        ws.set_max_file_size(Some(8));
+        assert_eq!(ws.load(&big).unwrap_err().code(), 2010);
+        assert_eq!(ws.editor_count(), 0);
+
+        ws.load_with(&big, true).unwrap();
+        ws.load_with(&nul, true).unwrap();
+        assert_eq!(ws.editor(&nul).unwrap().all_lines(), ["a\0b"]);
+        assert!(!ws.editor(&nul).unwrap().is_readonly());
+        // 有损转换过的内容不能写回
+        ws.load_with(&latin1, true).unwrap();
+        assert_eq!(ws.editor(&latin1).unwrap().all_lines(), ["caf\u{fffd}"]);
+        assert_eq!(ws.save_file(&latin1, true).unwrap_err().code(), 2008);
+
+        // 重新读取磁盘内容时同样给出专门的错误，而不是 IO 错误
+        assert_eq!(ws.disk_lines(&latin1).unwrap_err().code(), 2009);
+        assert_eq!(ws.disk_lines(&big).unwrap_err().code(), 2010);
+        assert_eq!(ws.reload(&latin1, false).unwrap_err().code(), 2009);
+        ws.reload(&latin1, true).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn files_without_write_permission_open_readonly() {
+        use std::os::unix::fs::PermissionsExt;
+        let (mut ws, _tmp) = new_temp_workspace();
+        let path = ws.resolve_path(Some("locked.txt"));
+        fs::write(&path, "x\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o444)).unwrap();
+        assert!(is_writable(&ws.resolve_path(Some("missing.txt"))));
+        // root 不受权限位限制，文件照样可写，测不出来
+        if is_writable(&path) {
+            return;
+        }
+        ws.load(&path).unwrap();
+        assert!(ws.editor(&path).unwrap().is_readonly());
+
+        // 恢复写权限后重新加载即可编辑
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        ws.load(&path).unwrap();
+        assert!(!ws.editor(&path).unwrap().is_readonly());
+    }
+
+    #[test]
+    fn root_discovery_prefers_explicit_then_env_then_marker() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("proj");
+        let nested = root.join("src/deep");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(WORKSPACE_MARKER), "{}").unwrap();
+
+        let explicit = tmp.path().join("elsewhere");
+        let env = OsStr::new("/from/env");
+        assert_eq!(Workspace::discover_root(Some(&explicit), Some(env), &nested), explicit);
+        assert_eq!(Workspace::discover_root(None, Some(env), &nested), PathBuf::from("/from/env"));
+        assert_eq!(Workspace::discover_root(None, Some(OsStr::new("")), &nested), root);
+        assert_eq!(Workspace::discover_root(None, None, &nested), root);
+        assert_eq!(Workspace::discover_root(None, None, tmp.path()), PathBuf::from(DEFAULT_BASE_DIR));
+    }
+
+    #[test]
+    fn sandbox_rejects_paths_outside_root() {
+        let (mut ws, tmp) = new_temp_workspace();
+        let outside = tmp.path().join("outside.txt");
+        fs::write(&outside, "secret").unwrap();
+        ws.load(&outside).unwrap();
+
+        ws.set_sandbox(Some(Sandbox::new(&ws.base_dir, &[]).unwrap()));
+        let inside = ws.resolve_path(Some("ok.txt"));
+        ws.load(&inside).unwrap();
+        assert_eq!(ws.load(ws.resolve_path(Some("../outside.txt"))).unwrap_err().code(), 2007);
+        assert!(ws.init(&outside, false).is_err());
+        // 沙箱开启前打开的外部文件也不能再写回
+        assert!(ws.save_file(&outside, false).is_err());
+        assert!(ws.save_all(false).is_err());
+        ws.save_file(&inside, false).unwrap();
+    }
+
+    #[test]
+    fn registers_round_trip_through_memento() {
+        let (mut ws, _tmp) = new_temp_workspace();
+
+        ws.set_register('a', Register::Lines(vec!["x".into(), "y".into()])).unwrap();
+        ws.record_deleted(Register::Chars("gone".into()));
+        assert!(ws.set_register(DELETED_REGISTER, Register::Chars("no".into())).is_err());
+
+        // 无名寄存器总是跟着最近一次写入
+        assert_eq!(ws.get_register(UNNAMED_REGISTER).unwrap(), &Register::Chars("gone".into()));
+
+        let json = serde_json::to_string(&ws.to_memento()).unwrap();
+        let (mut restored, _tmp2) = new_temp_workspace();
+        restored.from_memento(serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(
+            restored.get_register('a').unwrap(),
+            &Register::Lines(vec!["x".into(), "y".into()])
+        );
+        assert_eq!(restored.get_register(DELETED_REGISTER).unwrap(), &Register::Chars("gone".into()));
+        assert!(restored.get_register('b').is_err());
+    }
+
     #[test]
     fn log_show_reads_dot_filename_log_under_base_dir() {
         let (ws, _tmp) = new_temp_workspace();